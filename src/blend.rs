@@ -2,25 +2,103 @@
 // See https://en.wikipedia.org/wiki/Alpha_compositing
 
 // from rust
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
 
 // from external crate
 
 // from local crate
-use error::RasterResult;
+use error::{RasterError, RasterResult};
+use image;
 use Image;
 use Color;
 
 /// Enumeration for blending modes.
-#[derive(Debug)]
+///
+/// Blend modes can also be parsed from their lowercase, hyphenated names.
+///
+/// # Examples
+/// ```
+/// use raster::BlendMode;
+///
+/// let mode: BlendMode = "color-dodge".parse().unwrap();
+/// assert_eq!(BlendMode::ColorDodge, mode);
+/// assert_eq!("color-dodge", mode.to_string());
+///
+/// assert!("colour-dodge".parse::<BlendMode>().is_err());
+/// ```
+#[derive(Debug, PartialEq)]
 pub enum BlendMode {
     Normal,
+    Addition,
+    ColorBurn,
+    ColorDodge,
+    Darken,
     Difference,
+    Exclusion,
+    HardLight,
+    Lighten,
     Multiply,
     Overlay,
     Screen,
+    SoftLight,
+    Subtract,
 }
 
-pub fn difference(
+/// Parse a blend mode from its name, eg. "normal" or "soft-light".
+///
+/// # Errors
+///
+/// Unknown names fail with `RasterError::InvalidBlendMode`.
+impl FromStr for BlendMode {
+    type Err = RasterError;
+
+    fn from_str(s: &str) -> RasterResult<BlendMode> {
+        match s {
+            "normal" => Ok(BlendMode::Normal),
+            "addition" => Ok(BlendMode::Addition),
+            "color-burn" => Ok(BlendMode::ColorBurn),
+            "color-dodge" => Ok(BlendMode::ColorDodge),
+            "darken" => Ok(BlendMode::Darken),
+            "difference" => Ok(BlendMode::Difference),
+            "exclusion" => Ok(BlendMode::Exclusion),
+            "hard-light" => Ok(BlendMode::HardLight),
+            "lighten" => Ok(BlendMode::Lighten),
+            "multiply" => Ok(BlendMode::Multiply),
+            "overlay" => Ok(BlendMode::Overlay),
+            "screen" => Ok(BlendMode::Screen),
+            "soft-light" => Ok(BlendMode::SoftLight),
+            "subtract" => Ok(BlendMode::Subtract),
+            _ => Err(RasterError::InvalidBlendMode(s.to_string())),
+        }
+    }
+}
+
+/// Display the blend mode using the same name accepted by `from_str`.
+impl fmt::Display for BlendMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            BlendMode::Normal => "normal",
+            BlendMode::Addition => "addition",
+            BlendMode::ColorBurn => "color-burn",
+            BlendMode::ColorDodge => "color-dodge",
+            BlendMode::Darken => "darken",
+            BlendMode::Difference => "difference",
+            BlendMode::Exclusion => "exclusion",
+            BlendMode::HardLight => "hard-light",
+            BlendMode::Lighten => "lighten",
+            BlendMode::Multiply => "multiply",
+            BlendMode::Overlay => "overlay",
+            BlendMode::Screen => "screen",
+            BlendMode::SoftLight => "soft-light",
+            BlendMode::Subtract => "subtract",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+pub fn addition(
     image1: &Image,
     image2: &Image,
     loop_start_y: i32,
@@ -31,41 +109,62 @@ pub fn difference(
     offset_y: i32,
     opacity: f32,
 ) -> RasterResult<Image> {
-    let mut canvas = image1.clone();
+    blend_pixels(
+        image1,
+        image2,
+        loop_start_y..loop_end_y,
+        loop_start_x..loop_end_x,
+        offset_x,
+        offset_y,
+        |rgba1, rgba2| blend_color(rgba1, rgba2, BlendFunction::Addition, opacity),
+    )
+}
 
-    for y in loop_start_y..loop_end_y {
-        for x in loop_start_x..loop_end_x {
-            let canvas_x = x + offset_x;
-            let canvas_y = y + offset_y;
-            let rgba1 = image1.get_pixel(canvas_x, canvas_y)?;
-            let a1 = rgba1.a as f32 / 255.0; // convert to 0.0 - 1.0
-            let r1 = rgba1.r as f32 * a1;
-            let g1 = rgba1.g as f32 * a1;
-            let b1 = rgba1.b as f32 * a1;
-
-            let rgba2 = image2.get_pixel(x, y)?;
-            let a2 = rgba2.a as f32 / 255.0 * opacity; // convert to 0.0 - 1.0
-            let r2 = rgba2.r as f32;
-            let g2 = rgba2.g as f32;
-            let b2 = rgba2.b as f32;
-
-            let r3 = ch_alpha_f(r1, r2, BlendFunction::Difference, a2);
-            let g3 = ch_alpha_f(g1, g2, BlendFunction::Difference, a2);
-            let b3 = ch_alpha_f(b1, b2, BlendFunction::Difference, a2);
-            let a3 = 255;
-
-            canvas.set_pixel(
-                canvas_x,
-                canvas_y,
-                &Color::rgba(r3 as u8, g3 as u8, b3 as u8, a3 as u8),
-            )?;
-        }
-    }
+pub fn color_burn(
+    image1: &Image,
+    image2: &Image,
+    loop_start_y: i32,
+    loop_end_y: i32,
+    loop_start_x: i32,
+    loop_end_x: i32,
+    offset_x: i32,
+    offset_y: i32,
+    opacity: f32,
+) -> RasterResult<Image> {
+    blend_pixels(
+        image1,
+        image2,
+        loop_start_y..loop_end_y,
+        loop_start_x..loop_end_x,
+        offset_x,
+        offset_y,
+        |rgba1, rgba2| blend_color(rgba1, rgba2, BlendFunction::ColorBurn, opacity),
+    )
+}
 
-    Ok(canvas)
+pub fn color_dodge(
+    image1: &Image,
+    image2: &Image,
+    loop_start_y: i32,
+    loop_end_y: i32,
+    loop_start_x: i32,
+    loop_end_x: i32,
+    offset_x: i32,
+    offset_y: i32,
+    opacity: f32,
+) -> RasterResult<Image> {
+    blend_pixels(
+        image1,
+        image2,
+        loop_start_y..loop_end_y,
+        loop_start_x..loop_end_x,
+        offset_x,
+        offset_y,
+        |rgba1, rgba2| blend_color(rgba1, rgba2, BlendFunction::ColorDodge, opacity),
+    )
 }
 
-pub fn multiply(
+pub fn darken(
     image1: &Image,
     image2: &Image,
     loop_start_y: i32,
@@ -76,41 +175,62 @@ pub fn multiply(
     offset_y: i32,
     opacity: f32,
 ) -> RasterResult<Image> {
-    let mut canvas = image1.clone();
+    blend_pixels(
+        image1,
+        image2,
+        loop_start_y..loop_end_y,
+        loop_start_x..loop_end_x,
+        offset_x,
+        offset_y,
+        |rgba1, rgba2| blend_color(rgba1, rgba2, BlendFunction::Darken, opacity),
+    )
+}
 
-    for y in loop_start_y..loop_end_y {
-        for x in loop_start_x..loop_end_x {
-            let canvas_x = x + offset_x;
-            let canvas_y = y + offset_y;
-            let rgba1 = image1.get_pixel(canvas_x, canvas_y)?;
-            let a1 = rgba1.a as f32 / 255.0; // convert to 0.0 - 1.0
-            let r1 = rgba1.r as f32 * a1;
-            let g1 = rgba1.g as f32 * a1;
-            let b1 = rgba1.b as f32 * a1;
-
-            let rgba2 = image2.get_pixel(x, y)?;
-            let a2 = rgba2.a as f32 / 255.0 * opacity; // convert to 0.0 - 1.0
-            let r2 = rgba2.r as f32;
-            let g2 = rgba2.g as f32;
-            let b2 = rgba2.b as f32;
-
-            let r3 = ch_alpha_f(r1, r2, BlendFunction::Multiply, a2);
-            let g3 = ch_alpha_f(g1, g2, BlendFunction::Multiply, a2);
-            let b3 = ch_alpha_f(b1, b2, BlendFunction::Multiply, a2);
-            let a3 = 255;
-
-            canvas.set_pixel(
-                canvas_x,
-                canvas_y,
-                &Color::rgba(r3 as u8, g3 as u8, b3 as u8, a3 as u8),
-            )?;
-        }
-    }
+pub fn difference(
+    image1: &Image,
+    image2: &Image,
+    loop_start_y: i32,
+    loop_end_y: i32,
+    loop_start_x: i32,
+    loop_end_x: i32,
+    offset_x: i32,
+    offset_y: i32,
+    opacity: f32,
+) -> RasterResult<Image> {
+    blend_pixels(
+        image1,
+        image2,
+        loop_start_y..loop_end_y,
+        loop_start_x..loop_end_x,
+        offset_x,
+        offset_y,
+        |rgba1, rgba2| blend_color(rgba1, rgba2, BlendFunction::Difference, opacity),
+    )
+}
 
-    Ok(canvas)
+pub fn exclusion(
+    image1: &Image,
+    image2: &Image,
+    loop_start_y: i32,
+    loop_end_y: i32,
+    loop_start_x: i32,
+    loop_end_x: i32,
+    offset_x: i32,
+    offset_y: i32,
+    opacity: f32,
+) -> RasterResult<Image> {
+    blend_pixels(
+        image1,
+        image2,
+        loop_start_y..loop_end_y,
+        loop_start_x..loop_end_x,
+        offset_x,
+        offset_y,
+        |rgba1, rgba2| blend_color(rgba1, rgba2, BlendFunction::Exclusion, opacity),
+    )
 }
 
-pub fn normal(
+pub fn hard_light(
     image1: &Image,
     image2: &Image,
     loop_start_y: i32,
@@ -121,41 +241,62 @@ pub fn normal(
     offset_y: i32,
     opacity: f32,
 ) -> RasterResult<Image> {
-    let mut canvas = image1.clone();
+    blend_pixels(
+        image1,
+        image2,
+        loop_start_y..loop_end_y,
+        loop_start_x..loop_end_x,
+        offset_x,
+        offset_y,
+        |rgba1, rgba2| blend_color(rgba1, rgba2, BlendFunction::HardLight, opacity),
+    )
+}
 
-    for y in loop_start_y..loop_end_y {
-        for x in loop_start_x..loop_end_x {
-            let canvas_x = x + offset_x;
-            let canvas_y = y + offset_y;
-            let color1 = image1.get_pixel(canvas_x, canvas_y)?;
-            let a1 = color1.a as f32 / 255.0; // convert to 0.0 - 1.0
-            let r1 = color1.r as f32 * a1;
-            let g1 = color1.g as f32 * a1;
-            let b1 = color1.b as f32 * a1;
-
-            let color2 = image2.get_pixel(x, y)?;
-            let a2 = color2.a as f32 / 255.0 * opacity; // convert to 0.0 - 1.0
-            let r2 = color2.r as f32;
-            let g2 = color2.g as f32;
-            let b2 = color2.b as f32;
-
-            let r3 = (a2 * r2) + ((1.0 - a2) * r1);
-            let g3 = (a2 * g2) + ((1.0 - a2) * g1);
-            let b3 = (a2 * b2) + ((1.0 - a2) * b1);
-            let a3 = 255;
-
-            canvas.set_pixel(
-                canvas_x,
-                canvas_y,
-                &Color::rgba(r3 as u8, g3 as u8, b3 as u8, a3 as u8),
-            )?;
-        }
-    }
+pub fn lighten(
+    image1: &Image,
+    image2: &Image,
+    loop_start_y: i32,
+    loop_end_y: i32,
+    loop_start_x: i32,
+    loop_end_x: i32,
+    offset_x: i32,
+    offset_y: i32,
+    opacity: f32,
+) -> RasterResult<Image> {
+    blend_pixels(
+        image1,
+        image2,
+        loop_start_y..loop_end_y,
+        loop_start_x..loop_end_x,
+        offset_x,
+        offset_y,
+        |rgba1, rgba2| blend_color(rgba1, rgba2, BlendFunction::Lighten, opacity),
+    )
+}
 
-    Ok(canvas)
+pub fn multiply(
+    image1: &Image,
+    image2: &Image,
+    loop_start_y: i32,
+    loop_end_y: i32,
+    loop_start_x: i32,
+    loop_end_x: i32,
+    offset_x: i32,
+    offset_y: i32,
+    opacity: f32,
+) -> RasterResult<Image> {
+    blend_pixels(
+        image1,
+        image2,
+        loop_start_y..loop_end_y,
+        loop_start_x..loop_end_x,
+        offset_x,
+        offset_y,
+        |rgba1, rgba2| blend_color(rgba1, rgba2, BlendFunction::Multiply, opacity),
+    )
 }
 
-pub fn overlay(
+pub fn normal(
     image1: &Image,
     image2: &Image,
     loop_start_y: i32,
@@ -166,38 +307,57 @@ pub fn overlay(
     offset_y: i32,
     opacity: f32,
 ) -> RasterResult<Image> {
-    let mut canvas = image1.clone();
+    blend_pixels(
+        image1,
+        image2,
+        loop_start_y..loop_end_y,
+        loop_start_x..loop_end_x,
+        offset_x,
+        offset_y,
+        |color1, color2| normal_color(color1, color2, opacity),
+    )
+}
 
-    for y in loop_start_y..loop_end_y {
-        for x in loop_start_x..loop_end_x {
-            let canvas_x = x + offset_x;
-            let canvas_y = y + offset_y;
-            let rgba1 = image1.get_pixel(canvas_x, canvas_y)?;
-            let a1 = rgba1.a as f32 / 255.0; // convert to 0.0 - 1.0
-            let r1 = rgba1.r as f32 * a1;
-            let g1 = rgba1.g as f32 * a1;
-            let b1 = rgba1.b as f32 * a1;
-
-            let rgba2 = image2.get_pixel(x, y)?;
-            let a2 = rgba2.a as f32 / 255.0 * opacity; // convert to 0.0 - 1.0
-            let r2 = rgba2.r as f32;
-            let g2 = rgba2.g as f32;
-            let b2 = rgba2.b as f32;
-
-            let r3 = ch_alpha_f(r1, r2, BlendFunction::Overlay, a2);
-            let g3 = ch_alpha_f(g1, g2, BlendFunction::Overlay, a2);
-            let b3 = ch_alpha_f(b1, b2, BlendFunction::Overlay, a2);
-            let a3 = 255;
-
-            canvas.set_pixel(
-                canvas_x,
-                canvas_y,
-                &Color::rgba(r3 as u8, g3 as u8, b3 as u8, a3 as u8),
-            )?;
-        }
-    }
+// The normal blend of a single top color over a base color. The result is always opaque.
+pub fn normal_color(color1: &Color, color2: &Color, opacity: f32) -> Color {
+    let a1 = color1.a as f32 / 255.0; // convert to 0.0 - 1.0
+    let r1 = color1.r as f32 * a1;
+    let g1 = color1.g as f32 * a1;
+    let b1 = color1.b as f32 * a1;
 
-    Ok(canvas)
+    let a2 = color2.a as f32 / 255.0 * opacity; // convert to 0.0 - 1.0
+    let r2 = color2.r as f32;
+    let g2 = color2.g as f32;
+    let b2 = color2.b as f32;
+
+    let r3 = (a2 * r2) + ((1.0 - a2) * r1);
+    let g3 = (a2 * g2) + ((1.0 - a2) * g1);
+    let b3 = (a2 * b2) + ((1.0 - a2) * b1);
+    let a3 = 255;
+
+    Color::rgba(r3 as u8, g3 as u8, b3 as u8, a3 as u8)
+}
+
+pub fn overlay(
+    image1: &Image,
+    image2: &Image,
+    loop_start_y: i32,
+    loop_end_y: i32,
+    loop_start_x: i32,
+    loop_end_x: i32,
+    offset_x: i32,
+    offset_y: i32,
+    opacity: f32,
+) -> RasterResult<Image> {
+    blend_pixels(
+        image1,
+        image2,
+        loop_start_y..loop_end_y,
+        loop_start_x..loop_end_x,
+        offset_x,
+        offset_y,
+        |rgba1, rgba2| blend_color(rgba1, rgba2, BlendFunction::Overlay, opacity),
+    )
 }
 
 pub fn screen(
@@ -211,38 +371,62 @@ pub fn screen(
     offset_y: i32,
     opacity: f32,
 ) -> RasterResult<Image> {
-    let mut canvas = image1.clone();
+    blend_pixels(
+        image1,
+        image2,
+        loop_start_y..loop_end_y,
+        loop_start_x..loop_end_x,
+        offset_x,
+        offset_y,
+        |rgba1, rgba2| blend_color(rgba1, rgba2, BlendFunction::Screen, opacity),
+    )
+}
 
-    for y in loop_start_y..loop_end_y {
-        for x in loop_start_x..loop_end_x {
-            let canvas_x = x + offset_x;
-            let canvas_y = y + offset_y;
-            let rgba1 = image1.get_pixel(canvas_x, canvas_y)?;
-            let a1 = rgba1.a as f32 / 255.0; // convert to 0.0 - 1.0
-            let r1 = rgba1.r as f32 * a1;
-            let g1 = rgba1.g as f32 * a1;
-            let b1 = rgba1.b as f32 * a1;
-
-            let rgba2 = image2.get_pixel(x, y)?;
-            let a2 = rgba2.a as f32 / 255.0 * opacity; // convert to 0.0 - 1.0
-            let r2 = rgba2.r as f32;
-            let g2 = rgba2.g as f32;
-            let b2 = rgba2.b as f32;
-
-            let r3 = ch_alpha_f(r1, r2, BlendFunction::Screen, a2);
-            let g3 = ch_alpha_f(g1, g2, BlendFunction::Screen, a2);
-            let b3 = ch_alpha_f(b1, b2, BlendFunction::Screen, a2);
-            let a3 = 255;
-
-            canvas.set_pixel(
-                canvas_x,
-                canvas_y,
-                &Color::rgba(r3 as u8, g3 as u8, b3 as u8, a3 as u8),
-            )?;
-        }
-    }
+/// Soft light using the W3C compositing formula, not the Pegtop variant.
+///
+/// See https://www.w3.org/TR/compositing-1/#blendingsoftlight
+pub fn soft_light(
+    image1: &Image,
+    image2: &Image,
+    loop_start_y: i32,
+    loop_end_y: i32,
+    loop_start_x: i32,
+    loop_end_x: i32,
+    offset_x: i32,
+    offset_y: i32,
+    opacity: f32,
+) -> RasterResult<Image> {
+    blend_pixels(
+        image1,
+        image2,
+        loop_start_y..loop_end_y,
+        loop_start_x..loop_end_x,
+        offset_x,
+        offset_y,
+        |rgba1, rgba2| blend_color(rgba1, rgba2, BlendFunction::SoftLight, opacity),
+    )
+}
 
-    Ok(canvas)
+pub fn subtract(
+    image1: &Image,
+    image2: &Image,
+    loop_start_y: i32,
+    loop_end_y: i32,
+    loop_start_x: i32,
+    loop_end_x: i32,
+    offset_x: i32,
+    offset_y: i32,
+    opacity: f32,
+) -> RasterResult<Image> {
+    blend_pixels(
+        image1,
+        image2,
+        loop_start_y..loop_end_y,
+        loop_start_x..loop_end_x,
+        offset_x,
+        offset_y,
+        |rgba1, rgba2| blend_color(rgba1, rgba2, BlendFunction::Subtract, opacity),
+    )
 }
 
 // PRIVATE FNs
@@ -254,20 +438,98 @@ This is the private BlendFunction enum, not to be confused with BlendMode, which
 consumption! BlendFunction differs only in lacking a Normal variant, as ch_alpha_f has no need for
 such things.
 */
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 enum BlendFunction {
+    Addition,
+    ColorBurn,
+    ColorDodge,
+    Darken,
     Difference,
+    Exclusion,
+    HardLight,
+    Lighten,
     Multiply,
     Overlay,
     Screen,
+    SoftLight,
+    Subtract,
+}
+
+// Blend the pixels of image2 over the pixels of image1 inside the rows and columns one by one,
+// using f which gets the base and top colors. With the rayon feature, rows are blended in
+// parallel.
+fn blend_pixels<F>(
+    image1: &Image,
+    image2: &Image,
+    rows: Range<i32>,
+    columns: Range<i32>,
+    offset_x: i32,
+    offset_y: i32,
+    f: F,
+) -> RasterResult<Image>
+where
+    F: Fn(&Color, &Color) -> Color + Send + Sync,
+{
+    let mut canvas = image1.clone();
+
+    image::fill_rows(&mut canvas, |canvas_y, row| {
+        let y = canvas_y - offset_y;
+        if !rows.contains(&y) {
+            return;
+        }
+        for x in columns.clone() {
+            let base = ((x + offset_x) * 4) as usize;
+            let top = ((y * image2.width + x) * 4) as usize;
+            let rgba1 = Color::rgba(row[base], row[base + 1], row[base + 2], row[base + 3]);
+            let rgba2 = Color::rgba(
+                image2.bytes[top],
+                image2.bytes[top + 1],
+                image2.bytes[top + 2],
+                image2.bytes[top + 3],
+            );
+
+            let rgba3 = f(&rgba1, &rgba2);
+            row[base..base + 4].copy_from_slice(&[rgba3.r, rgba3.g, rgba3.b, rgba3.a]);
+        }
+    });
+
+    Ok(canvas)
+}
+
+fn blend_color(rgba1: &Color, rgba2: &Color, f: BlendFunction, opacity: f32) -> Color {
+    let a1 = rgba1.a as f32 / 255.0; // convert to 0.0 - 1.0
+    let r1 = rgba1.r as f32 * a1;
+    let g1 = rgba1.g as f32 * a1;
+    let b1 = rgba1.b as f32 * a1;
+
+    let a2 = rgba2.a as f32 / 255.0 * opacity; // convert to 0.0 - 1.0
+    let r2 = rgba2.r as f32;
+    let g2 = rgba2.g as f32;
+    let b2 = rgba2.b as f32;
+
+    let r3 = ch_alpha_f(r1, r2, f, a2);
+    let g3 = ch_alpha_f(g1, g2, f, a2);
+    let b3 = ch_alpha_f(b1, b2, f, a2);
+    let a3 = 255;
+
+    Color::rgba(r3 as u8, g3 as u8, b3 as u8, a3 as u8)
 }
 
 fn ch_alpha_f(base: f32, top: f32, f: BlendFunction, opacity: f32) -> f32 {
     match f {
+        BlendFunction::Addition => ch_alpha(base, ch_addition(base, top), opacity),
+        BlendFunction::ColorBurn => ch_alpha(base, ch_color_burn(base, top), opacity),
+        BlendFunction::ColorDodge => ch_alpha(base, ch_color_dodge(base, top), opacity),
+        BlendFunction::Darken => ch_alpha(base, ch_darken(base, top), opacity),
         BlendFunction::Difference => ch_alpha(base, ch_difference(base, top), opacity),
+        BlendFunction::Exclusion => ch_alpha(base, ch_exclusion(base, top), opacity),
+        BlendFunction::HardLight => ch_alpha(base, ch_hard_light(base, top), opacity),
+        BlendFunction::Lighten => ch_alpha(base, ch_lighten(base, top), opacity),
         BlendFunction::Multiply => ch_alpha(base, ch_multiply(base, top), opacity),
         BlendFunction::Overlay => ch_alpha(base, ch_overlay(base, top), opacity),
         BlendFunction::Screen => ch_alpha(base, ch_screen(base, top), opacity),
+        BlendFunction::SoftLight => ch_alpha(base, ch_soft_light(base, top), opacity),
+        BlendFunction::Subtract => ch_alpha(base, ch_subtract(base, top), opacity),
     }
 }
 
@@ -275,10 +537,47 @@ fn ch_alpha(base: f32, top: f32, opacity: f32) -> f32 {
     (opacity * top) + ((1.0 - opacity) * base)
 }
 
+fn ch_addition(base: f32, top: f32) -> f32 {
+    (base + top).min(255.0)
+}
+
+fn ch_color_burn(base: f32, top: f32) -> f32 {
+    if top <= 0.0 {
+        0.0 // Avoid division by zero
+    } else {
+        (255.0 - ((255.0 - base) * 255.0 / top)).max(0.0)
+    }
+}
+
+fn ch_color_dodge(base: f32, top: f32) -> f32 {
+    if top >= 255.0 {
+        255.0 // Avoid division by zero
+    } else {
+        (base * 255.0 / (255.0 - top)).min(255.0)
+    }
+}
+
+fn ch_darken(base: f32, top: f32) -> f32 {
+    base.min(top)
+}
+
 fn ch_difference(base: f32, top: f32) -> f32 {
     (base - top).abs()
 }
 
+fn ch_exclusion(base: f32, top: f32) -> f32 {
+    base + top - 2.0 * base * top / 255.0
+}
+
+// Overlay with base and top swapped.
+fn ch_hard_light(base: f32, top: f32) -> f32 {
+    ch_overlay(top, base)
+}
+
+fn ch_lighten(base: f32, top: f32) -> f32 {
+    base.max(top)
+}
+
 fn ch_multiply(base: f32, top: f32) -> f32 {
     (base * top) / 255.0
 }
@@ -294,3 +593,24 @@ fn ch_overlay(base: f32, top: f32) -> f32 {
 fn ch_screen(base: f32, top: f32) -> f32 {
     255.0 - (((255.0 - base) * (255.0 - top)) / 255.0)
 }
+
+// W3C soft light, see https://www.w3.org/TR/compositing-1/#blendingsoftlight
+fn ch_soft_light(base: f32, top: f32) -> f32 {
+    let base = base / 255.0;
+    let top = top / 255.0;
+    let result = if top <= 0.5 {
+        base - (1.0 - 2.0 * top) * base * (1.0 - base)
+    } else {
+        let d = if base <= 0.25 {
+            ((16.0 * base - 12.0) * base + 4.0) * base
+        } else {
+            base.sqrt()
+        };
+        base + (2.0 * top - 1.0) * (d - base)
+    };
+    result * 255.0
+}
+
+fn ch_subtract(base: f32, top: f32) -> f32 {
+    (base - top).max(0.0)
+}
@@ -2,12 +2,22 @@
 
 // from rust
 use std;
+use std::cmp;
 
 // from external crate
 
 // from local crate
 use error::{RasterError, RasterResult};
 
+// D65 reference white in CIE XYZ.
+const WHITE_X: f32 = 0.950_47;
+const WHITE_Y: f32 = 1.0;
+const WHITE_Z: f32 = 1.088_83;
+
+// Constants of the CIELAB companding function, (6/29)^3 and 6/29.
+const LAB_EPSILON: f32 = 0.008_856;
+const LAB_DELTA: f32 = 6.0 / 29.0;
+
 /// A struct for representing and creating color.
 #[derive(Debug, Clone)]
 pub struct Color {
@@ -45,6 +55,179 @@ impl<'a> Color {
         }
     }
 
+    /// Returns a cyan Color.
+    pub fn cyan() -> Color {
+        Color {
+            r: 0,
+            g: 255,
+            b: 255,
+            a: 255,
+        }
+    }
+
+    /// Get the CIE76 color difference (Delta E) to another color. Alpha is ignored.
+    ///
+    /// This is the Euclidean distance between the colors in CIELAB, see `to_lab`, so it follows
+    /// perceived differences better than `distance`. A Delta E of about 2.3 is the smallest
+    /// difference most people notice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Color;
+    ///
+    /// let delta_e = Color::rgb(200, 30, 30).delta_e(&Color::rgb(201, 30, 31));
+    ///
+    /// assert!(delta_e < 1.0);
+    /// ```
+    pub fn delta_e(&self, other: &Color) -> f32 {
+        let (l1, a1, b1) = self.to_lab();
+        let (l2, a2, b2) = other.to_lab();
+        ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+    }
+
+    /// Get the Euclidean distance to another color in RGB space. Alpha is ignored.
+    ///
+    /// Ranges from 0.0 for identical colors to about 441.67 between black and white.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Color;
+    ///
+    /// let distance = Color::rgb(0, 0, 0).distance(&Color::rgb(3, 4, 0));
+    ///
+    /// assert_eq!(5.0, distance);
+    /// ```
+    pub fn distance(&self, other: &Color) -> f32 {
+        let dr = self.r as f32 - other.r as f32;
+        let dg = self.g as f32 - other.g as f32;
+        let db = self.b as f32 - other.b as f32;
+        (dr * dr + dg * dg + db * db).sqrt()
+    }
+
+    /// Get the Euclidean distance to another color in RGBA space.
+    ///
+    /// Same as `distance` but alpha counts as a fourth channel, so it ranges up to 510.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Color;
+    ///
+    /// let distance = Color::rgba(0, 0, 0, 255).distance_rgba(&Color::rgba(0, 0, 0, 0));
+    ///
+    /// assert_eq!(255.0, distance);
+    /// ```
+    pub fn distance_rgba(&self, other: &Color) -> f32 {
+        let da = self.a as f32 - other.a as f32;
+        (self.distance(other).powi(2) + da * da).sqrt()
+    }
+
+    /// Create a color from CMYK (Cyan, Magenta, Yellow, Key). Alpha defaults to opaque (255).
+    ///
+    /// All values are 0.0 - 1.0. This is the naive conversion without a color profile, see
+    /// `to_cmyk`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Color;
+    ///
+    /// let color = Color::from_cmyk(0.0, 1.0, 1.0, 0.0); // Red
+    ///
+    /// assert_eq!(255, color.r);
+    /// assert_eq!(0, color.g);
+    /// assert_eq!(0, color.b);
+    /// assert_eq!(255, color.a);
+    /// ```
+    pub fn from_cmyk(c: f32, m: f32, y: f32, k: f32) -> Color {
+        let channel = |v: f32| (255.0 * (1.0 - v) * (1.0 - k)).round().clamp(0.0, 255.0) as u8;
+        Color::rgb(channel(c), channel(m), channel(y))
+    }
+
+    /// Create a color from HSL (Hue, Saturation, Lightness). Alpha defaults to opaque (255).
+    ///
+    /// Hue is in degrees 0.0 - 360.0, saturation and lightness are 0.0 - 1.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Color;
+    ///
+    /// let color = Color::from_hsl(0.0, 1.0, 0.5); // Red
+    ///
+    /// assert_eq!(255, color.r);
+    /// assert_eq!(0, color.g);
+    /// assert_eq!(0, color.b);
+    /// assert_eq!(255, color.a);
+    /// ```
+    pub fn from_hsl(h: f32, s: f32, l: f32) -> Color {
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        from_hue(h, chroma, l - chroma / 2.0)
+    }
+
+    /// Create a color from HSV/HSB (Hue, Saturation, Brightness). Alpha defaults to opaque (255).
+    ///
+    /// Hue is in degrees 0.0 - 360.0, saturation and brightness are 0.0 - 1.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Color;
+    ///
+    /// let color = Color::from_hsv(120.0, 1.0, 1.0); // Green
+    ///
+    /// assert_eq!(0, color.r);
+    /// assert_eq!(255, color.g);
+    /// assert_eq!(0, color.b);
+    /// assert_eq!(255, color.a);
+    /// ```
+    pub fn from_hsv(h: f32, s: f32, v: f32) -> Color {
+        let chroma = v * s;
+        from_hue(h, chroma, v - chroma)
+    }
+
+    /// Create a color from CIELAB. Alpha defaults to opaque (255).
+    ///
+    /// The inverse of `to_lab`, using the same sRGB gamma and D65 white point. Colors outside the
+    /// sRGB gamut are clamped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Color;
+    ///
+    /// let color = Color::from_lab(100.0, 0.0, 0.0); // White
+    ///
+    /// assert_eq!(255, color.r);
+    /// assert_eq!(255, color.g);
+    /// assert_eq!(255, color.b);
+    /// assert_eq!(255, color.a);
+    /// ```
+    pub fn from_lab(l: f32, a: f32, b: f32) -> Color {
+        let fy = (l + 16.0) / 116.0;
+        let x = WHITE_X * lab_f_inverse(fy + a / 500.0);
+        let y = WHITE_Y * lab_f_inverse(fy);
+        let z = WHITE_Z * lab_f_inverse(fy - b / 200.0);
+
+        let r = 3.240_454_2 * x - 1.537_138_5 * y - 0.498_531_4 * z;
+        let g = -0.969_266 * x + 1.876_010_8 * y + 0.041_556 * z;
+        let b = 0.055_643_4 * x - 0.204_025_9 * y + 1.057_225_2 * z;
+
+        Color::rgb(linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b))
+    }
+
+    /// Returns a gray Color.
+    pub fn gray() -> Color {
+        Color {
+            r: 128,
+            g: 128,
+            b: 128,
+            a: 255,
+        }
+    }
+
     /// Returns a green Color.
     pub fn green() -> Color {
         Color {
@@ -118,6 +301,141 @@ impl<'a> Color {
         }
     }
 
+    /// Get the HSL (Hue, Saturation, Lightness) of this color. Alpha is ignored.
+    ///
+    /// Hue is in degrees 0.0 - 360.0, saturation and lightness are 0.0 - 1.0. Grays have no hue
+    /// and no saturation, so 0.0 is returned for both.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Color;
+    ///
+    /// let (h, s, l) = Color::rgb(255, 0, 0).hsl(); // Red
+    ///
+    /// assert_eq!(0.0, h);
+    /// assert_eq!(1.0, s);
+    /// assert_eq!(0.5, l);
+    /// ```
+    pub fn hsl(&self) -> (f32, f32, f32) {
+        let r = self.r as f32 / 255.0;
+        let g = self.g as f32 / 255.0;
+        let b = self.b as f32 / 255.0;
+
+        let max = rgb_max(r, g, b);
+        let min = rgb_min(r, g, b);
+        let chroma = max - min;
+
+        let l = (max + min) / 2.0;
+        let s = if chroma > 0.0 {
+            chroma / (1.0 - (2.0 * l - 1.0).abs())
+        } else {
+            0.0
+        };
+
+        (hue(r, g, b, max, chroma), s, l)
+    }
+
+    /// Get the HSV/HSB (Hue, Saturation, Brightness) of this color. Alpha is ignored.
+    ///
+    /// Hue is in degrees 0.0 - 360.0, saturation and brightness are 0.0 - 1.0. Grays have no
+    /// hue, so 0.0 is returned for them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Color;
+    ///
+    /// let (h, s, v) = Color::rgb(0, 0, 255).hsv(); // Blue
+    ///
+    /// assert_eq!(240.0, h);
+    /// assert_eq!(1.0, s);
+    /// assert_eq!(1.0, v);
+    /// ```
+    pub fn hsv(&self) -> (f32, f32, f32) {
+        let r = self.r as f32 / 255.0;
+        let g = self.g as f32 / 255.0;
+        let b = self.b as f32 / 255.0;
+
+        let max = rgb_max(r, g, b);
+        let chroma = max - rgb_min(r, g, b);
+
+        let s = if max > 0.0 { chroma / max } else { 0.0 };
+
+        (hue(r, g, b, max, chroma), s, max)
+    }
+
+    /// Linearly interpolate between two colors, including alpha.
+    ///
+    /// A `t` of 0.0 returns `a` and 1.0 returns `b`. Values outside 0.0 - 1.0 are clamped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Color;
+    ///
+    /// let color = Color::lerp(&Color::black(), &Color::white(), 0.5);
+    ///
+    /// assert_eq!(128, color.r);
+    /// assert_eq!(128, color.g);
+    /// assert_eq!(128, color.b);
+    /// assert_eq!(255, color.a);
+    /// ```
+    pub fn lerp(a: &Color, b: &Color, t: f32) -> Color {
+        let t = t.clamp(0.0, 1.0);
+
+        Color {
+            r: _lerp(a.r, b.r, t),
+            g: _lerp(a.g, b.g, t),
+            b: _lerp(a.b, b.b, t),
+            a: _lerp(a.a, b.a, t),
+        }
+    }
+
+    /// Get the perceived brightness of this color using the Rec. 601 luma weights. Alpha is
+    /// ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Color;
+    ///
+    /// assert_eq!(255, Color::white().luminance());
+    /// assert_eq!(150, Color::green().luminance());
+    /// ```
+    pub fn luminance(&self) -> u8 {
+        (0.299 * self.r as f32 + 0.587 * self.g as f32 + 0.114 * self.b as f32).round() as u8
+    }
+
+    /// Returns a magenta Color.
+    pub fn magenta() -> Color {
+        Color {
+            r: 255,
+            g: 0,
+            b: 255,
+            a: 255,
+        }
+    }
+
+    /// Multiply RGB by alpha, as expected by renderers that blend with premultiplied alpha.
+    ///
+    /// Each channel becomes `channel * alpha / 255`, rounded. Alpha is unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Color;
+    ///
+    /// let color = Color::rgba(255, 255, 255, 128).premultiply();
+    ///
+    /// assert_eq!(128, color.r);
+    /// assert_eq!(128, color.a);
+    /// ```
+    pub fn premultiply(&self) -> Color {
+        let channel = |v: u8| ((v as u32 * self.a as u32 + 127) / 255) as u8;
+        Color::rgba(channel(self.r), channel(self.g), channel(self.b), self.a)
+    }
+
     /// Returns a red Color.
     pub fn red() -> Color {
         Color {
@@ -168,6 +486,59 @@ impl<'a> Color {
         Color { r, g, b, a }
     }
 
+    /// Convert to CMYK (Cyan, Magenta, Yellow, Key). Alpha is ignored.
+    ///
+    /// All values are 0.0 - 1.0. This is the naive conversion without a color profile, so the
+    /// result is only an approximation of what a printer would use. Black has a key of 1.0 and
+    /// 0.0 for the other values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Color;
+    ///
+    /// let (c, m, y, k) = Color::red().to_cmyk();
+    ///
+    /// assert_eq!((0.0, 1.0, 1.0, 0.0), (c, m, y, k));
+    /// ```
+    pub fn to_cmyk(&self) -> (f32, f32, f32, f32) {
+        let r = self.r as f32 / 255.0;
+        let g = self.g as f32 / 255.0;
+        let b = self.b as f32 / 255.0;
+
+        let k = 1.0 - rgb_max(r, g, b);
+        if k >= 1.0 {
+            return (0.0, 0.0, 0.0, 1.0);
+        }
+
+        (
+            (1.0 - r - k) / (1.0 - k),
+            (1.0 - g - k) / (1.0 - k),
+            (1.0 - b - k) / (1.0 - k),
+            k,
+        )
+    }
+
+    /// Convert the color to a lowercase hexadecimal string. The inverse of `Color::hex`.
+    ///
+    /// The format is #rrggbb for opaque colors and #rrggbbaa when alpha is less than 255.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Color;
+    ///
+    /// assert_eq!("#1a2b3c", Color::hex("#1A2B3C").unwrap().to_hex());
+    /// assert_eq!("#00ff007f", Color::rgba(0, 255, 0, 127).to_hex());
+    /// ```
+    pub fn to_hex(&self) -> String {
+        if self.a < 255 {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        } else {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        }
+    }
+
     /// Convert RGB to HSV/HSB (Hue, Saturation, Brightness).
     ///
     /// ```
@@ -219,6 +590,38 @@ impl<'a> Color {
         (h.round() as u16, s * 100.0, v * 100.0)
     }
 
+    /// Convert to CIELAB, the perceptually uniform L*a*b* color space. Alpha is ignored.
+    ///
+    /// The color is assumed to be sRGB, with the sRGB gamma curve, and is converted through CIE
+    /// XYZ using the D65 white point. L is 0.0 - 100.0, while a and b are roughly -128.0 - 128.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Color;
+    ///
+    /// let (l, a, b) = Color::white().to_lab();
+    ///
+    /// assert_eq!(100, l.round() as i32);
+    /// assert_eq!(0, a.round() as i32);
+    /// assert_eq!(0, b.round() as i32);
+    /// ```
+    pub fn to_lab(&self) -> (f32, f32, f32) {
+        let r = srgb_to_linear(self.r);
+        let g = srgb_to_linear(self.g);
+        let b = srgb_to_linear(self.b);
+
+        let x = 0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b;
+        let y = 0.212_672_9 * r + 0.715_152_2 * g + 0.072_175 * b;
+        let z = 0.019_333_9 * r + 0.119_192 * g + 0.950_304_1 * b;
+
+        let fx = lab_f(x / WHITE_X);
+        let fy = lab_f(y / WHITE_Y);
+        let fz = lab_f(z / WHITE_Z);
+
+        (116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+    }
+
     /// Convert HSV/HSB (Hue, Saturation, Brightness) to RGB.
     ///
     /// ```
@@ -286,6 +689,41 @@ impl<'a> Color {
         )
     }
 
+    /// Returns a fully transparent black Color.
+    pub fn transparent() -> Color {
+        Color {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 0,
+        }
+    }
+
+    /// Divide RGB by alpha. The inverse of `premultiply`.
+    ///
+    /// Each channel becomes `channel * 255 / alpha`, rounded and clamped to 255. A fully
+    /// transparent color has no color left to recover and becomes transparent black. Rounding in
+    /// `premultiply` loses precision at low alpha, so the round trip is not always exact.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Color;
+    ///
+    /// let color = Color::rgba(128, 128, 128, 128).unpremultiply();
+    ///
+    /// assert_eq!(255, color.r);
+    /// assert_eq!(128, color.a);
+    /// ```
+    pub fn unpremultiply(&self) -> Color {
+        if self.a == 0 {
+            return Color::transparent();
+        }
+        let a = self.a as u32;
+        let channel = |v: u8| cmp::min(255, (v as u32 * 255 + a / 2) / a) as u8;
+        Color::rgba(channel(self.r), channel(self.g), channel(self.b), self.a)
+    }
+
     /// Returns a white Color.
     pub fn white() -> Color {
         Color {
@@ -295,10 +733,108 @@ impl<'a> Color {
             a: 255,
         }
     }
+
+    /// Returns a yellow Color.
+    pub fn yellow() -> Color {
+        Color {
+            r: 255,
+            g: 255,
+            b: 0,
+            a: 255,
+        }
+    }
 }
 
 // Private functions
 
+// Convert an sRGB channel to linear light in 0.0 - 1.0.
+fn srgb_to_linear(value: u8) -> f32 {
+    let v = value as f32 / 255.0;
+    if v <= 0.040_45 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+// Convert linear light to an sRGB channel, clamping values outside 0.0 - 1.0.
+fn linear_to_srgb(value: f32) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    let v = if v <= 0.003_130_8 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    (v * 255.0).round() as u8
+}
+
+// The CIELAB companding function.
+fn lab_f(t: f32) -> f32 {
+    if t > LAB_EPSILON {
+        t.cbrt()
+    } else {
+        t / (3.0 * LAB_DELTA * LAB_DELTA) + 4.0 / 29.0
+    }
+}
+
+// The inverse of lab_f.
+fn lab_f_inverse(t: f32) -> f32 {
+    if t > LAB_DELTA {
+        t * t * t
+    } else {
+        3.0 * LAB_DELTA * LAB_DELTA * (t - 4.0 / 29.0)
+    }
+}
+
+// Get the hue in degrees 0.0 - 360.0 from RGB in 0.0 - 1.0. Grays have a hue of 0.0.
+fn hue(r: f32, g: f32, b: f32, max: f32, chroma: f32) -> f32 {
+    if chroma <= 0.0 {
+        return 0.0;
+    }
+
+    let h = if (max - r).abs() < std::f32::EPSILON {
+        ((g - b) / chroma) % 6.0
+    } else if (max - g).abs() < std::f32::EPSILON {
+        ((b - r) / chroma) + 2.0
+    } else {
+        ((r - g) / chroma) + 4.0
+    };
+
+    let h = h * 60.0;
+    if h < 0.0 {
+        h + 360.0
+    } else {
+        h
+    }
+}
+
+// Create a color from a hue in degrees, the chroma and the amount to add to each channel to
+// match lightness or brightness. All except hue are 0.0 - 1.0.
+fn from_hue(h: f32, chroma: f32, m: f32) -> Color {
+    let h = (((h % 360.0) + 360.0) % 360.0) / 60.0;
+    let x = chroma * (1.0 - ((h % 2.0) - 1.0).abs());
+
+    let (r, g, b) = if h < 1.0 {
+        (chroma, x, 0.0)
+    } else if h < 2.0 {
+        (x, chroma, 0.0)
+    } else if h < 3.0 {
+        (0.0, chroma, x)
+    } else if h < 4.0 {
+        (0.0, x, chroma)
+    } else if h < 5.0 {
+        (x, 0.0, chroma)
+    } else {
+        (chroma, 0.0, x)
+    };
+
+    Color::rgb(
+        ((r + m) * 255.0).round() as u8,
+        ((g + m) * 255.0).round() as u8,
+        ((b + m) * 255.0).round() as u8,
+    )
+}
+
 // Convert a hex string to decimal. Eg. "00" -> 0. "FF" -> 255.
 fn _hex_dec(hex_string: &str) -> RasterResult<u8> {
     u8::from_str_radix(hex_string, 16)
@@ -306,6 +842,14 @@ fn _hex_dec(hex_string: &str) -> RasterResult<u8> {
         .map_err(RasterError::HexParse)
 }
 
+// Linear interpolation of a single channel, t is 0.0 - 1.0.
+fn _lerp(a: u8, b: u8, t: f32) -> u8 {
+    let a = a as f32;
+    let b = b as f32;
+
+    (a + (t * (b - a))).round() as u8
+}
+
 fn rgb_min(r: f32, g: f32, b: f32) -> f32 {
     let min = if g < r { g } else { r };
 
@@ -2,6 +2,9 @@
 
 // from rust
 use std::cmp;
+use std::f64;
+use std::fmt;
+use std::str::FromStr;
 
 // from external crate
 
@@ -9,9 +12,31 @@ use std::cmp;
 use error::{RasterError, RasterResult};
 use blend::{self, BlendMode};
 use Color;
+use image;
 use Image;
+use interpolate::{resample, InterpolationMode};
 use position::{Position, PositionMode};
-use transform;
+
+/// Add a border around an image, growing it by the thickness on all four sides.
+///
+/// Unlike drawing a rectangle, the original pixels are kept and the canvas becomes larger.
+///
+/// # Errors
+///
+/// A negative thickness fails with `RasterError::InvalidPadding`.
+///
+/// # Examples
+/// ```
+/// use raster::{editor, Color};
+///
+/// let mut image = raster::open("tests/in/sample.png").unwrap();
+/// editor::add_border(&mut image, 10, Color::hex("#333333").unwrap()).unwrap();
+/// raster::save(&image, "tests/out/test_add_border.png").unwrap();
+/// ```
+///
+pub fn add_border(src: &mut Image, thickness: i32, color: Color) -> RasterResult<()> {
+    pad(src, thickness, thickness, thickness, thickness, color)
+}
 
 /// Blend 2 images into one. The image1 is the base and image2 is the top.
 ///
@@ -22,7 +47,8 @@ use transform;
 /// # Errors
 ///
 /// If image2 falls outside the canvas area, then this fails with
-/// `RasterError::BlendingImageFallsOutsideCanvas`.
+/// `RasterError::BlendingImageFallsOutsideCanvas`, which holds the computed position of image2
+/// and the dimensions of both images.
 ///
 /// # Examples
 /// ```
@@ -37,17 +63,35 @@ use transform;
 /// let normal = editor::blend(&image1, &image2, BlendMode::Normal, 1.0, PositionMode::Center, 0, 0).unwrap();
 ///
 /// // All the other blend modes
+/// let addition = editor::blend(&image1, &image2, BlendMode::Addition, 1.0, PositionMode::Center, 0, 0).unwrap();
+/// let color_burn = editor::blend(&image1, &image2, BlendMode::ColorBurn, 1.0, PositionMode::Center, 0, 0).unwrap();
+/// let color_dodge = editor::blend(&image1, &image2, BlendMode::ColorDodge, 1.0, PositionMode::Center, 0, 0).unwrap();
+/// let darken = editor::blend(&image1, &image2, BlendMode::Darken, 1.0, PositionMode::Center, 0, 0).unwrap();
 /// let difference = editor::blend(&image1, &image2, BlendMode::Difference, 1.0, PositionMode::Center, 0, 0).unwrap();
+/// let exclusion = editor::blend(&image1, &image2, BlendMode::Exclusion, 1.0, PositionMode::Center, 0, 0).unwrap();
+/// let hard_light = editor::blend(&image1, &image2, BlendMode::HardLight, 1.0, PositionMode::Center, 0, 0).unwrap();
+/// let lighten = editor::blend(&image1, &image2, BlendMode::Lighten, 1.0, PositionMode::Center, 0, 0).unwrap();
 /// let multiply = editor::blend(&image1, &image2, BlendMode::Multiply, 1.0, PositionMode::Center, 0, 0).unwrap();
 /// let overlay = editor::blend(&image1, &image2, BlendMode::Overlay, 1.0, PositionMode::Center, 0, 0).unwrap();
 /// let screen = editor::blend(&image1, &image2, BlendMode::Screen, 1.0, PositionMode::Center, 0, 0).unwrap();
+/// let soft_light = editor::blend(&image1, &image2, BlendMode::SoftLight, 1.0, PositionMode::Center, 0, 0).unwrap();
+/// let subtract = editor::blend(&image1, &image2, BlendMode::Subtract, 1.0, PositionMode::Center, 0, 0).unwrap();
 ///
 /// // Save it
 /// raster::save(&normal, "tests/out/test_blend_normal.png").unwrap();
+/// raster::save(&addition, "tests/out/test_blend_addition.png").unwrap();
+/// raster::save(&color_burn, "tests/out/test_blend_color_burn.png").unwrap();
+/// raster::save(&color_dodge, "tests/out/test_blend_color_dodge.png").unwrap();
+/// raster::save(&darken, "tests/out/test_blend_darken.png").unwrap();
 /// raster::save(&difference, "tests/out/test_blend_difference.png").unwrap();
+/// raster::save(&exclusion, "tests/out/test_blend_exclusion.png").unwrap();
+/// raster::save(&hard_light, "tests/out/test_blend_hard_light.png").unwrap();
+/// raster::save(&lighten, "tests/out/test_blend_lighten.png").unwrap();
 /// raster::save(&multiply, "tests/out/test_blend_multiply.png").unwrap();
 /// raster::save(&overlay, "tests/out/test_blend_overlay.png").unwrap();
 /// raster::save(&screen, "tests/out/test_blend_screen.png").unwrap();
+/// raster::save(&soft_light, "tests/out/test_blend_soft_light.png").unwrap();
+/// raster::save(&subtract, "tests/out/test_blend_subtract.png").unwrap();
 /// ```
 /// ### Source Images
 ///
@@ -65,11 +109,46 @@ use transform;
 ///
 /// ![](https://kosinix.github.io/raster/out/test_blend_normal.png)
 ///
+/// Addition
+///
+/// ![](https://kosinix.github.io/raster/out/test_blend_addition.png)
+///
+///
+/// Color Burn
+///
+/// ![](https://kosinix.github.io/raster/out/test_blend_color_burn.png)
+///
+///
+/// Color Dodge
+///
+/// ![](https://kosinix.github.io/raster/out/test_blend_color_dodge.png)
+///
+///
+/// Darken
+///
+/// ![](https://kosinix.github.io/raster/out/test_blend_darken.png)
+///
+///
 /// Difference
 ///
 /// ![](https://kosinix.github.io/raster/out/test_blend_difference.png)
 ///
 ///
+/// Exclusion
+///
+/// ![](https://kosinix.github.io/raster/out/test_blend_exclusion.png)
+///
+///
+/// Hard Light
+///
+/// ![](https://kosinix.github.io/raster/out/test_blend_hard_light.png)
+///
+///
+/// Lighten
+///
+/// ![](https://kosinix.github.io/raster/out/test_blend_lighten.png)
+///
+///
 /// Multiply
 ///
 /// ![](https://kosinix.github.io/raster/out/test_blend_multiply.png)
@@ -84,6 +163,16 @@ use transform;
 ///
 /// ![](https://kosinix.github.io/raster/out/test_blend_screen.png)
 ///
+///
+/// Soft Light
+///
+/// ![](https://kosinix.github.io/raster/out/test_blend_soft_light.png)
+///
+///
+/// Subtract
+///
+/// ![](https://kosinix.github.io/raster/out/test_blend_subtract.png)
+///
 pub fn blend(
     image1: &Image,
     image2: &Image,
@@ -113,7 +202,11 @@ pub fn blend(
 
     // Check if it overlaps
     if (offset_x >= w1) || (offset_x + w2 <= 0) || (offset_y >= h1) || (offset_y + h2 <= 0) {
-        return Err(RasterError::BlendingImageFallsOutsideCanvas);
+        return Err(RasterError::BlendingImageFallsOutsideCanvas {
+            offset: (offset_x, offset_y),
+            canvas: (w1, h1),
+            image: (w2, h2),
+        });
     }
 
     // Loop start X
@@ -160,6 +253,50 @@ pub fn blend(
             offset_y,
             opacity,
         ),
+        BlendMode::Addition => blend::addition(
+            image1,
+            image2,
+            loop_start_y,
+            loop_end_y,
+            loop_start_x,
+            loop_end_x,
+            offset_x,
+            offset_y,
+            opacity,
+        ),
+        BlendMode::ColorBurn => blend::color_burn(
+            image1,
+            image2,
+            loop_start_y,
+            loop_end_y,
+            loop_start_x,
+            loop_end_x,
+            offset_x,
+            offset_y,
+            opacity,
+        ),
+        BlendMode::ColorDodge => blend::color_dodge(
+            image1,
+            image2,
+            loop_start_y,
+            loop_end_y,
+            loop_start_x,
+            loop_end_x,
+            offset_x,
+            offset_y,
+            opacity,
+        ),
+        BlendMode::Darken => blend::darken(
+            image1,
+            image2,
+            loop_start_y,
+            loop_end_y,
+            loop_start_x,
+            loop_end_x,
+            offset_x,
+            offset_y,
+            opacity,
+        ),
         BlendMode::Difference => blend::difference(
             image1,
             image2,
@@ -171,6 +308,39 @@ pub fn blend(
             offset_y,
             opacity,
         ),
+        BlendMode::Exclusion => blend::exclusion(
+            image1,
+            image2,
+            loop_start_y,
+            loop_end_y,
+            loop_start_x,
+            loop_end_x,
+            offset_x,
+            offset_y,
+            opacity,
+        ),
+        BlendMode::HardLight => blend::hard_light(
+            image1,
+            image2,
+            loop_start_y,
+            loop_end_y,
+            loop_start_x,
+            loop_end_x,
+            offset_x,
+            offset_y,
+            opacity,
+        ),
+        BlendMode::Lighten => blend::lighten(
+            image1,
+            image2,
+            loop_start_y,
+            loop_end_y,
+            loop_start_x,
+            loop_end_x,
+            offset_x,
+            offset_y,
+            opacity,
+        ),
         BlendMode::Multiply => blend::multiply(
             image1,
             image2,
@@ -204,7 +374,68 @@ pub fn blend(
             offset_y,
             opacity,
         ),
+        BlendMode::SoftLight => blend::soft_light(
+            image1,
+            image2,
+            loop_start_y,
+            loop_end_y,
+            loop_start_x,
+            loop_end_x,
+            offset_x,
+            offset_y,
+            opacity,
+        ),
+        BlendMode::Subtract => blend::subtract(
+            image1,
+            image2,
+            loop_start_y,
+            loop_end_y,
+            loop_start_x,
+            loop_end_x,
+            offset_x,
+            offset_y,
+            opacity,
+        ),
+    }
+}
+
+/// Make the pixels close to a key color transparent, eg. to remove a green screen.
+///
+/// A pixel whose RGB channels are all within the tolerance of the key gets an alpha of 0. Pixels
+/// up to twice the tolerance away are feathered, their alpha scaled down proportionally to how
+/// close they are to the key. Everything else keeps its alpha.
+///
+/// Images are always stored as RGBA, so opaque sources like JPEG simply start out with an alpha of
+/// 255 everywhere. Save the result as PNG to keep the transparency, since JPEG drops the alpha
+/// channel.
+///
+/// # Examples
+/// ```
+/// use raster::{editor, Color};
+///
+/// let mut image = raster::open("tests/in/bnw.png").unwrap();
+/// editor::color_to_alpha(&mut image, Color::white(), 40).unwrap();
+/// raster::save(&image, "tests/out/test_color_to_alpha.png").unwrap();
+/// ```
+///
+pub fn color_to_alpha(src: &mut Image, key: Color, tolerance: u8) -> RasterResult<()> {
+    let tolerance = tolerance as u32;
+    for y in 0..src.height {
+        for x in 0..src.width {
+            let mut p = src.get_pixel(x, y)?;
+            let distance = channel_distance(&p, &key, false) as u32;
+            if distance <= tolerance {
+                p.a = 0;
+            } else if distance < tolerance * 2 {
+                p.a = (p.a as u32 * (distance - tolerance) / tolerance) as u8;
+            } else {
+                continue;
+            }
+            src.set_pixel(x, y, &p)?;
+        }
     }
+
+    Ok(())
 }
 
 /// Crop the image to the given dimension and position.
@@ -280,132 +511,704 @@ pub fn crop(
     offset_x: i32,
     offset_y: i32,
 ) -> RasterResult<()> {
-    // Turn into positioner struct
-    let positioner = Position::new(position, offset_x, offset_y);
-
-    let (offset_x, offset_y) = positioner.get_x_y(src.width, src.height, crop_width, crop_height)?;
-    let offset_x = cmp::max(0, offset_x);
-    let offset_y = cmp::max(0, offset_y);
-
-    let height2 = {
-        let height2 = offset_y + crop_height;
-        cmp::min(height2, src.height)
-    };
-
-    let width2 = {
-        let width2 = offset_x + crop_width;
-        cmp::min(width2, src.width)
-    };
+    let (offset_x, offset_y, dest_width, dest_height) =
+        crop_region(src, crop_width, crop_height, position, offset_x, offset_y)?;
 
-    let mut dest = Image::blank(width2 - offset_x, height2 - offset_y);
-
-    for y in 0..dest.height {
-        for x in 0..dest.width {
-            let pixel = src.get_pixel(offset_x + x, offset_y + y)?;
-            dest.set_pixel(x, y, &Color::rgba(pixel.r, pixel.g, pixel.b, pixel.a))?;
-        }
+    // Move the rows of the crop region to the front of the existing buffer. A destination row
+    // never starts after its source row, so copying top to bottom never overwrites pixels that
+    // are yet to be read.
+    let row_len = dest_width as usize * 4;
+    for y in 0..dest_height {
+        let start = ((offset_y + y) * src.width + offset_x) as usize * 4;
+        src.bytes.copy_within(start..start + row_len, y as usize * row_len);
     }
-    src.width = dest.width;
-    src.height = dest.height;
-    src.bytes = dest.bytes;
+    src.bytes.truncate(row_len * dest_height as usize);
+    src.width = dest_width;
+    src.height = dest_height;
 
     Ok(())
 }
 
-/// Fill an image with color.
+/// Crop the image to the largest centered square, eg. for avatars.
+///
+/// The side of the square is the smaller of the width and height. When the margins can't be
+/// equal, the extra pixel is on the right or bottom.
 ///
 /// # Examples
 /// ```
-/// use raster::Image;
 /// use raster::editor;
-/// use raster::Color;
 ///
-/// // Create a 100x100 image
-/// let mut image = Image::blank(100, 100);
-///
-/// // Fill it with red
-/// editor::fill(&mut image, Color::red()).unwrap();
-///
-/// // Save it
-/// raster::save(&image, "tests/out/test_fill.png").unwrap();
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// editor::crop_square(&mut image).unwrap();
+/// raster::save(&image, "tests/out/test_crop_square.jpg").unwrap();
 /// ```
 ///
-///
-pub fn fill(src: &mut Image, color: Color) -> RasterResult<()> {
-    for y in 0..src.height {
-        for x in 0..src.width {
-            src.set_pixel(x, y, &color)?;
-        }
-    }
-
-    Ok(())
-}
-
-/// An enum for the various modes that can be used for resizing.
-#[derive(Debug)]
-pub enum ResizeMode {
-    /// Resize image to exact dimensions ignoring aspect ratio.
-    Exact,
-    /// Resize image to exact width. Height parameter is ignored and is auto calculated instead.
-    ExactWidth,
-    /// Resize image to exact height. Width parameter is ignored and is auto calculated instead.
-    ExactHeight,
-    /// Resize an image to fit within the given width and height.
-    Fit,
-    /// Resize image to fill all the space in the given dimension. Excess parts are cropped.
-    Fill,
+pub fn crop_square(src: &mut Image) -> RasterResult<()> {
+    let side = cmp::min(src.width, src.height);
+    crop(src, side, side, PositionMode::Center, 0, 0)
 }
 
-/// Resize an image to a given width, height and mode.
+/// Crop the image to the given dimension and position, returning the result as a new image.
+///
+/// This works exactly like `editor::crop` but leaves the source image untouched.
 ///
 /// # Examples
-/// ### Resize Fit
-/// ```
-/// use raster::{editor, Color, Image, ResizeMode, BlendMode, PositionMode};
 ///
-/// // Create an image from file
-/// let mut image1 = raster::open("tests/in/sample.jpg").unwrap();
-/// let mut image2 = raster::open("tests/in/portrait.jpg").unwrap();
+/// ```
+/// use raster::{editor, PositionMode};
 ///
-/// // Resize it
-/// editor::resize(&mut image1, 200, 200, ResizeMode::Fit).unwrap();
-/// editor::resize(&mut image2, 200, 200, ResizeMode::Fit).unwrap();
+/// // Create image from file
+/// let image = raster::open("tests/in/crop-test.jpg").unwrap();
 ///
-/// // Superimpose images on a gray background
-/// let mut bg = Image::blank(200, 200);
-/// editor::fill(&mut bg, Color::hex("#CCCCCC").unwrap()).unwrap();
+/// // Crop the center into a new image
+/// let center = editor::crop_to(&image, 166, 93, PositionMode::Center, 0, 0).unwrap();
 ///
-/// let image1 = editor::blend(&bg, &image1, BlendMode::Normal, 1.0, PositionMode::TopLeft, 0, 0).unwrap();
-/// let image2 = editor::blend(&bg, &image2, BlendMode::Normal, 1.0, PositionMode::TopLeft, 0, 0).unwrap();
+/// assert_eq!(166, center.width);
+/// assert_eq!(93, center.height);
 ///
-/// raster::save(&image1, "tests/out/test_resize_fit_1.jpg").unwrap();
-/// raster::save(&image2, "tests/out/test_resize_fit_2.jpg").unwrap();
+/// raster::save(&center, "tests/out/test_crop_to_center.jpg").unwrap();
 /// ```
+pub fn crop_to(
+    src: &Image,
+    crop_width: i32,
+    crop_height: i32,
+    position: PositionMode,
+    offset_x: i32,
+    offset_y: i32,
+) -> RasterResult<Image> {
+    let (offset_x, offset_y, dest_width, dest_height) =
+        crop_region(src, crop_width, crop_height, position, offset_x, offset_y)?;
+
+    let row_len = dest_width as usize * 4;
+    let mut bytes = Vec::with_capacity(row_len * dest_height as usize);
+    for y in 0..dest_height {
+        let start = ((offset_y + y) * src.width + offset_x) as usize * 4;
+        bytes.extend_from_slice(&src.bytes[start..start + row_len]);
+    }
+
+    Ok(Image {
+        width: dest_width,
+        height: dest_height,
+        bytes,
+    })
+}
+
+/// Create an image that shows where two images of the same size differ, for debugging
+/// regressions.
 ///
-/// The gray box shows the 200x200 imaginary box that the images "fit" in.
+/// Pixels are opaque red where any RGBA channel differs and opaque black where the pixels are
+/// identical. Use `Image::compare` to measure how large the difference is.
 ///
+/// # Errors
 ///
-/// ![](https://kosinix.github.io/raster/out/test_resize_fit_1.jpg) ![](https://kosinix.github.io/raster/out/test_resize_fit_2.jpg)
+/// If the images differ in size, this fails with `RasterError::DimensionMismatch`.
 ///
-/// ### Resize Fill
+/// # Examples
 /// ```
-/// use raster::{editor, Color, Image, ResizeMode};
-///
-/// // Create an image from file
-/// let mut image1 = raster::open("tests/in/sample.jpg").unwrap();
-/// let mut image2 = raster::open("tests/in/portrait.jpg").unwrap();
-///
-/// // Resize it
-/// editor::resize(&mut image1, 200, 200, ResizeMode::Fill).unwrap();
-/// editor::resize(&mut image2, 200, 200, ResizeMode::Fill).unwrap();
+/// use raster::{editor, filter};
 ///
-/// raster::save(&image1, "tests/out/test_resize_fill_1.jpg").unwrap();
-/// raster::save(&image2, "tests/out/test_resize_fill_2.jpg").unwrap();
+/// let image1 = raster::open("tests/in/sample.png").unwrap();
+/// let mut image2 = image1.clone();
+/// filter::sharpen(&mut image2).unwrap();
+/// let diff = editor::diff_map(&image1, &image2).unwrap();
+/// raster::save(&diff, "tests/out/test_diff_map.png").unwrap();
 /// ```
 ///
-/// The image fills up the entire 200x200 box.
+pub fn diff_map(a: &Image, b: &Image) -> RasterResult<Image> {
+    image::check_dimensions(a, b)?;
+
+    // Blank images are opaque black
+    let mut dest = Image::blank(a.width, a.height);
+    for ((pixel, p1), p2) in dest
+        .bytes
+        .chunks_mut(4)
+        .zip(a.bytes.chunks(4))
+        .zip(b.bytes.chunks(4))
+    {
+        if p1 != p2 {
+            pixel[0] = 255;
+        }
+    }
+
+    Ok(dest)
+}
+
+/// Draw a circle centered on cx, cy using the midpoint circle algorithm, either as a 1 pixel
+/// outline or filled.
 ///
-/// ![](https://kosinix.github.io/raster/out/test_resize_fill_1.jpg) ![](https://kosinix.github.io/raster/out/test_resize_fill_2.jpg)
+/// Parts of the circle outside the image are clipped. A color with alpha is blended onto the
+/// existing pixels the same way as `BlendMode::Normal`. A negative radius draws nothing.
+///
+/// # Examples
+/// ```
+/// use raster::{editor, Color};
+///
+/// let mut image = raster::open("tests/in/sample.png").unwrap();
+/// editor::draw_circle(&mut image, 250, 125, 100, Color::rgba(255, 0, 0, 128), true).unwrap();
+/// editor::draw_circle(&mut image, 250, 125, 100, Color::red(), false).unwrap();
+/// raster::save(&image, "tests/out/test_draw_circle.png").unwrap();
+/// ```
+///
+pub fn draw_circle(
+    src: &mut Image,
+    cx: i32,
+    cy: i32,
+    radius: i32,
+    color: Color,
+    filled: bool,
+) -> RasterResult<()> {
+    let (cx, cy, r) = (cx as i64, cy as i64, radius as i64);
+    let (width, height) = (src.width as i64, src.height as i64);
+    if r < 0 || cx + r < 0 || cy + r < 0 || cx - r >= width || cy - r >= height {
+        return Ok(());
+    }
+
+    // The midpoint algorithm walks the first octant from x = r, y = 0 going counter-clockwise and
+    // stops after the last row where x >= y. At row y it is at the largest x with
+    // x * x - x + y * y < r * r, so each row of the circle can be computed on its own and only the
+    // rows inside the image are drawn.
+    let mut last = (r as f64 / f64::consts::SQRT_2) as i64 + 2;
+    while last > 0 && 2 * last * last - last >= r * r {
+        last -= 1;
+    }
+    let octant_x = |y: i64| {
+        if y == 0 {
+            r
+        } else {
+            largest_root(-1, r * r - y * y)
+        }
+    };
+    // The last row of the octant where x is at least the given x
+    let octant_y = |x: i64| cmp::min(last, largest_root(0, r * r - x * x + x));
+
+    for py in cmp::max(0, cy - r)..cmp::min(height, cy + r + 1) {
+        let dy = (py - cy).abs();
+        // Offsets from the center column to draw. Below the octant the row holds the octant
+        // point at row dy, above it a run of octant points mirrored across the diagonal.
+        let mut runs = Vec::new();
+        if filled {
+            let span = if dy <= last { octant_x(dy) } else { octant_y(dy) };
+            runs.push((0, span));
+        } else {
+            let (start, end) = (octant_y(dy + 1) + 1, octant_y(dy));
+            runs.push((start, end));
+            if dy <= last {
+                let x = octant_x(dy);
+                if x < start || x > end {
+                    runs.push((x, x));
+                }
+            }
+        }
+
+        for &(start, end) in &runs {
+            // Right of the center, then mirrored to the left without the center column again
+            for &(left, right) in &[(cx + start, cx + end), (cx - end, cx - cmp::max(start, 1))] {
+                for px in cmp::max(0, left)..cmp::min(width, right + 1) {
+                    draw_pixel(src, px as i32, py as i32, &color)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Draw a 1 pixel wide line from x0, y0 to x1, y1 using Bresenham's algorithm.
+///
+/// Parts of the line outside the image are clipped. A color with alpha is blended onto the
+/// existing pixels the same way as `BlendMode::Normal`.
+///
+/// # Examples
+/// ```
+/// use raster::{editor, Color};
+///
+/// let mut image = raster::open("tests/in/sample.png").unwrap();
+/// editor::draw_line(&mut image, 0, 0, 499, 249, Color::red()).unwrap();
+/// editor::draw_line(&mut image, 0, 249, 499, 0, Color::rgba(0, 0, 255, 128)).unwrap();
+/// raster::save(&image, "tests/out/test_draw_line.png").unwrap();
+/// ```
+///
+pub fn draw_line(
+    src: &mut Image,
+    x0: i32,
+    y0: i32,
+    x1: i32,
+    y1: i32,
+    color: Color,
+) -> RasterResult<()> {
+    match clip_line(src.width, src.height, x0, y0, x1, y1) {
+        Some((x0, y0, x1, y1)) => walk_line(x0, y0, x1, y1, |x, y| draw_pixel(src, x, y, &color)),
+        None => Ok(()),
+    }
+}
+
+/// Draw a closed polygon through the given points, either as a 1 pixel outline or filled.
+///
+/// Filling uses the even-odd rule, so concave and self-intersecting polygons are handled. A pixel
+/// is filled when its center is inside the polygon. Parts of the polygon outside the image are
+/// clipped. A color with alpha is blended onto the existing pixels the same way as
+/// `BlendMode::Normal`.
+///
+/// # Errors
+///
+/// Fewer than 3 points fail with `RasterError::InvalidPolygon`.
+///
+/// # Examples
+/// ```
+/// use raster::{editor, Color};
+///
+/// let mut image = raster::open("tests/in/sample.png").unwrap();
+/// let star = [(250, 20), (280, 100), (360, 100), (295, 150), (320, 230), (250, 180),
+///     (180, 230), (205, 150), (140, 100), (220, 100)];
+/// editor::draw_polygon(&mut image, &star, Color::rgba(255, 255, 0, 160), true).unwrap();
+/// editor::draw_polygon(&mut image, &star, Color::black(), false).unwrap();
+/// raster::save(&image, "tests/out/test_draw_polygon.png").unwrap();
+/// ```
+///
+pub fn draw_polygon(
+    src: &mut Image,
+    points: &[(i32, i32)],
+    color: Color,
+    filled: bool,
+) -> RasterResult<()> {
+    if points.len() < 3 {
+        return Err(RasterError::InvalidPolygon(points.len()));
+    }
+
+    // Each edge paired with the point before it, which closes the polygon.
+    let edges: Vec<((i32, i32), (i32, i32))> = points
+        .iter()
+        .enumerate()
+        .map(|(i, &end)| (points[(i + points.len() - 1) % points.len()], end))
+        .collect();
+
+    if filled {
+        let top = points.iter().map(|&(_, y)| y).min().unwrap_or(0);
+        let bottom = points.iter().map(|&(_, y)| y).max().unwrap_or(0);
+        for y in cmp::max(0, top)..cmp::min(src.height, bottom + 1) {
+            // Where the edges cross the center of this row
+            let center = y as f32 + 0.5;
+            let mut crossings: Vec<f32> = edges
+                .iter()
+                .filter(|&&((_, y0), (_, y1))| (y0 as f32 <= center) != (y1 as f32 <= center))
+                .map(|&((x0, y0), (x1, y1))| {
+                    x0 as f32 + (center - y0 as f32) * (x1 - x0) as f32 / (y1 - y0) as f32
+                })
+                .collect();
+            crossings.sort_by(|a, b| a.partial_cmp(b).unwrap_or(cmp::Ordering::Equal));
+
+            for span in crossings.chunks(2) {
+                if span.len() < 2 {
+                    break;
+                }
+                let start = cmp::max(0, (span[0] - 0.5).ceil() as i32);
+                let end = cmp::min(src.width, (span[1] - 0.5).ceil() as i32);
+                for x in start..end {
+                    draw_pixel(src, x, y, &color)?;
+                }
+            }
+        }
+    } else {
+        // Vertices are shared by two edges, so only draw them once.
+        let mut outline = Vec::new();
+        for &((x0, y0), (x1, y1)) in &edges {
+            if let Some((x0, y0, x1, y1)) = clip_line(src.width, src.height, x0, y0, x1, y1) {
+                walk_line(x0, y0, x1, y1, |x, y| {
+                    outline.push((x, y));
+                    Ok(())
+                })?;
+            }
+        }
+        outline.sort();
+        outline.dedup();
+        for (x, y) in outline {
+            draw_pixel(src, x, y, &color)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Draw a rectangle with its top left corner at x and y, either as a 1 pixel outline or filled.
+///
+/// Parts of the rectangle outside the image are clipped. A color with alpha is blended onto the
+/// existing pixels the same way as `BlendMode::Normal`.
+///
+/// # Examples
+/// ```
+/// use raster::{editor, Color};
+///
+/// let mut image = raster::open("tests/in/sample.png").unwrap();
+/// let fill = Color::rgba(255, 0, 0, 128);
+/// editor::draw_rectangle(&mut image, 50, 50, 200, 100, fill, true).unwrap();
+/// editor::draw_rectangle(&mut image, 50, 50, 200, 100, Color::red(), false).unwrap();
+/// raster::save(&image, "tests/out/test_draw_rectangle.png").unwrap();
+/// ```
+///
+pub fn draw_rectangle(
+    src: &mut Image,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    color: Color,
+    filled: bool,
+) -> RasterResult<()> {
+    let right = x + width - 1;
+    let bottom = y + height - 1;
+    for py in cmp::max(0, y)..cmp::min(src.height, y + height) {
+        for px in cmp::max(0, x)..cmp::min(src.width, x + width) {
+            if filled || px == x || px == right || py == y || py == bottom {
+                draw_pixel(src, px, py, &color)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Fill an image with color.
+///
+/// # Examples
+/// ```
+/// use raster::Image;
+/// use raster::editor;
+/// use raster::Color;
+///
+/// // Create a 100x100 image
+/// let mut image = Image::blank(100, 100);
+///
+/// // Fill it with red
+/// editor::fill(&mut image, Color::red()).unwrap();
+///
+/// // Save it
+/// raster::save(&image, "tests/out/test_fill.png").unwrap();
+/// ```
+///
+///
+pub fn fill(src: &mut Image, color: Color) -> RasterResult<()> {
+    for y in 0..src.height {
+        for x in 0..src.width {
+            src.set_pixel(x, y, &color)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Resize an image to fit in a box and pad it with a background color to exactly the box size,
+/// like "contain" in CSS.
+///
+/// The image is resized with `ResizeMode::Fit`, keeping its aspect ratio, and centered. When the
+/// padding can't be equal on both sides, the extra pixel goes to the right or bottom.
+///
+/// # Examples
+/// ```
+/// use raster::{editor, Color};
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// editor::fit_pad(&mut image, 200, 200, Color::white()).unwrap();
+/// raster::save(&image, "tests/out/test_fit_pad.jpg").unwrap();
+/// ```
+///
+pub fn fit_pad(src: &mut Image, width: i32, height: i32, background: Color) -> RasterResult<()> {
+    resize(src, width, height, ResizeMode::Fit)?;
+
+    let left = (width - src.width) / 2;
+    let top = (height - src.height) / 2;
+    let right = width - src.width - left;
+    let bottom = height - src.height - top;
+    pad(src, top, right, bottom, left, background)
+}
+
+/// Fill the contiguous region around a pixel with color, like a paint bucket.
+///
+/// Starting at x and y, every connected pixel whose channels, including alpha, are all within the
+/// tolerance of the starting pixel is replaced. A tolerance of 0 only fills the exact same color.
+///
+/// # Errors
+///
+/// If x or y falls outside the image, this fails with `RasterError::PixelOutOfBounds`.
+///
+/// # Examples
+/// ```
+/// use raster::{editor, Color};
+///
+/// let mut image = raster::open("tests/in/bnw.png").unwrap();
+/// editor::flood_fill(&mut image, 0, 0, Color::hex("#ff9900").unwrap(), 10).unwrap();
+/// raster::save(&image, "tests/out/test_flood_fill.png").unwrap();
+/// ```
+///
+pub fn flood_fill(src: &mut Image, x: i32, y: i32, fill: Color, tolerance: u8) -> RasterResult<()> {
+    let w = src.width;
+    let h = src.height;
+    if x < 0 || y < 0 || x >= w || y >= h {
+        return Err(RasterError::PixelOutOfBounds(x, y));
+    }
+
+    let seed = src.get_pixel(x, y)?;
+    let mut visited = vec![false; (w * h) as usize];
+    let mut stack = vec![(x, y)];
+    visited[(y * w + x) as usize] = true;
+
+    while let Some((x, y)) = stack.pop() {
+        src.set_pixel(x, y, &fill)?;
+
+        for &(nx, ny) in &[(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+            if nx < 0 || ny < 0 || nx >= w || ny >= h || visited[(ny * w + nx) as usize] {
+                continue;
+            }
+            if channel_distance(&src.get_pixel(nx, ny)?, &seed, true) <= tolerance {
+                visited[(ny * w + nx) as usize] = true;
+                stack.push((nx, ny));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// An enum for the directions of a gradient.
+#[derive(Debug)]
+pub enum GradientMode {
+    /// Left to right.
+    Horizontal,
+    /// Top to bottom.
+    Vertical,
+    /// Top left to bottom right.
+    Diagonal,
+}
+
+/// Fill an image with a linear gradient going from the start color to the end color.
+///
+/// # Examples
+/// ```
+/// use raster::{editor, Color, GradientMode, Image};
+///
+/// // Create a 200x100 image
+/// let mut horizontal = Image::blank(200, 100);
+/// let mut vertical = Image::blank(200, 100);
+///
+/// // Fill it with gradients
+/// editor::gradient(&mut horizontal, Color::red(), Color::blue(), GradientMode::Horizontal).unwrap();
+/// editor::gradient(&mut vertical, Color::white(), Color::black(), GradientMode::Vertical).unwrap();
+///
+/// // Save it
+/// raster::save(&horizontal, "tests/out/test_gradient_horizontal.png").unwrap();
+/// raster::save(&vertical, "tests/out/test_gradient_vertical.png").unwrap();
+/// ```
+///
+/// ![](https://kosinix.github.io/raster/out/test_gradient_horizontal.png)
+/// ![](https://kosinix.github.io/raster/out/test_gradient_vertical.png)
+///
+pub fn gradient(src: &mut Image, start: Color, end: Color, mode: GradientMode) -> RasterResult<()> {
+    // Distance from the start to the end of the gradient, in pixels.
+    let length = match mode {
+        GradientMode::Horizontal => src.width - 1,
+        GradientMode::Vertical => src.height - 1,
+        GradientMode::Diagonal => (src.width - 1) + (src.height - 1),
+    };
+    let length = cmp::max(1, length) as f32;
+
+    for y in 0..src.height {
+        for x in 0..src.width {
+            let distance = match mode {
+                GradientMode::Horizontal => x,
+                GradientMode::Vertical => y,
+                GradientMode::Diagonal => x + y,
+            };
+            let color = Color::lerp(&start, &end, distance as f32 / length);
+            src.set_pixel(x, y, &color)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Pad the edges of an image by different amounts, filling the new space with a color.
+///
+/// The canvas grows by the padding on each edge and the original pixels are moved accordingly. Use
+/// `crop` to remove space instead.
+///
+/// # Errors
+///
+/// Negative padding fails with `RasterError::InvalidPadding`.
+///
+/// # Examples
+/// ```
+/// use raster::{editor, Color};
+///
+/// let mut image = raster::open("tests/in/sample.png").unwrap();
+/// editor::pad(&mut image, 40, 0, 0, 20, Color::white()).unwrap();
+/// raster::save(&image, "tests/out/test_pad.png").unwrap();
+/// ```
+///
+pub fn pad(
+    src: &mut Image,
+    top: i32,
+    right: i32,
+    bottom: i32,
+    left: i32,
+    color: Color,
+) -> RasterResult<()> {
+    if let Some(&padding) = [top, right, bottom, left].iter().find(|&&p| p < 0) {
+        return Err(RasterError::InvalidPadding(padding));
+    }
+
+    let mut canvas = Image::blank(src.width + left + right, src.height + top + bottom);
+    fill(&mut canvas, color)?;
+
+    // Copy the original rows into place
+    let row_len = src.width as usize * 4;
+    for y in 0..src.height as usize {
+        let start = ((y + top as usize) * canvas.width as usize + left as usize) * 4;
+        canvas.bytes[start..start + row_len]
+            .copy_from_slice(&src.bytes[y * row_len..(y + 1) * row_len]);
+    }
+
+    *src = canvas;
+
+    Ok(())
+}
+
+/// Replace every pixel close to a color with another color, whether or not the pixels are
+/// connected.
+///
+/// A pixel matches when its RGB channels are all within the tolerance of the from color. Alpha is
+/// not compared, so a color matches regardless of its transparency. Matching pixels are set to the
+/// to color, including its alpha.
+///
+/// # Examples
+/// ```
+/// use raster::{editor, Color};
+///
+/// let mut image = raster::open("tests/in/bnw.png").unwrap();
+/// editor::replace_color(&mut image, Color::white(), Color::rgba(0, 0, 0, 0), 20).unwrap();
+/// raster::save(&image, "tests/out/test_replace_color.png").unwrap();
+/// ```
+///
+pub fn replace_color(src: &mut Image, from: Color, to: Color, tolerance: u8) -> RasterResult<()> {
+    for y in 0..src.height {
+        for x in 0..src.width {
+            if channel_distance(&src.get_pixel(x, y)?, &from, false) <= tolerance {
+                src.set_pixel(x, y, &to)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// An enum for the various modes that can be used for resizing.
+///
+/// Resize modes can also be parsed from their lowercase, hyphenated names.
+///
+/// # Examples
+/// ```
+/// use raster::ResizeMode;
+///
+/// let mode: ResizeMode = "exact-width".parse().unwrap();
+/// assert_eq!(ResizeMode::ExactWidth, mode);
+/// assert_eq!("exact-width", mode.to_string());
+/// ```
+#[derive(Debug, PartialEq)]
+pub enum ResizeMode {
+    /// Resize image to exact dimensions ignoring aspect ratio.
+    Exact,
+    /// Resize image to exact width. Height parameter is ignored and is auto calculated instead.
+    ExactWidth,
+    /// Resize image to exact height. Width parameter is ignored and is auto calculated instead.
+    ExactHeight,
+    /// Resize an image to fit within the given width and height.
+    Fit,
+    /// Resize image to fill all the space in the given dimension. Excess parts are cropped.
+    Fill,
+}
+
+/// Parse a resize mode from its name, eg. "fit" or "exact-width".
+///
+/// # Errors
+///
+/// Unknown names fail with `RasterError::InvalidResizeMode`.
+impl FromStr for ResizeMode {
+    type Err = RasterError;
+
+    fn from_str(s: &str) -> RasterResult<ResizeMode> {
+        match s {
+            "exact" => Ok(ResizeMode::Exact),
+            "exact-width" => Ok(ResizeMode::ExactWidth),
+            "exact-height" => Ok(ResizeMode::ExactHeight),
+            "fit" => Ok(ResizeMode::Fit),
+            "fill" => Ok(ResizeMode::Fill),
+            _ => Err(RasterError::InvalidResizeMode(s.to_string())),
+        }
+    }
+}
+
+/// Display the resize mode using the same name accepted by `from_str`.
+impl fmt::Display for ResizeMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            ResizeMode::Exact => "exact",
+            ResizeMode::ExactWidth => "exact-width",
+            ResizeMode::ExactHeight => "exact-height",
+            ResizeMode::Fit => "fit",
+            ResizeMode::Fill => "fill",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Resize an image to a given width, height and mode.
+///
+/// # Examples
+/// ### Resize Fit
+/// ```
+/// use raster::{editor, Color, Image, ResizeMode, BlendMode, PositionMode};
+///
+/// // Create an image from file
+/// let mut image1 = raster::open("tests/in/sample.jpg").unwrap();
+/// let mut image2 = raster::open("tests/in/portrait.jpg").unwrap();
+///
+/// // Resize it
+/// editor::resize(&mut image1, 200, 200, ResizeMode::Fit).unwrap();
+/// editor::resize(&mut image2, 200, 200, ResizeMode::Fit).unwrap();
+///
+/// // Superimpose images on a gray background
+/// let mut bg = Image::blank(200, 200);
+/// editor::fill(&mut bg, Color::hex("#CCCCCC").unwrap()).unwrap();
+///
+/// let image1 = editor::blend(&bg, &image1, BlendMode::Normal, 1.0, PositionMode::TopLeft, 0, 0).unwrap();
+/// let image2 = editor::blend(&bg, &image2, BlendMode::Normal, 1.0, PositionMode::TopLeft, 0, 0).unwrap();
+///
+/// raster::save(&image1, "tests/out/test_resize_fit_1.jpg").unwrap();
+/// raster::save(&image2, "tests/out/test_resize_fit_2.jpg").unwrap();
+/// ```
+///
+/// The gray box shows the 200x200 imaginary box that the images "fit" in.
+///
+///
+/// ![](https://kosinix.github.io/raster/out/test_resize_fit_1.jpg) ![](https://kosinix.github.io/raster/out/test_resize_fit_2.jpg)
+///
+/// ### Resize Fill
+/// ```
+/// use raster::{editor, Color, Image, ResizeMode};
+///
+/// // Create an image from file
+/// let mut image1 = raster::open("tests/in/sample.jpg").unwrap();
+/// let mut image2 = raster::open("tests/in/portrait.jpg").unwrap();
+///
+/// // Resize it
+/// editor::resize(&mut image1, 200, 200, ResizeMode::Fill).unwrap();
+/// editor::resize(&mut image2, 200, 200, ResizeMode::Fill).unwrap();
+///
+/// raster::save(&image1, "tests/out/test_resize_fill_1.jpg").unwrap();
+/// raster::save(&image2, "tests/out/test_resize_fill_2.jpg").unwrap();
+/// ```
+///
+/// The image fills up the entire 200x200 box.
+///
+/// ![](https://kosinix.github.io/raster/out/test_resize_fill_1.jpg) ![](https://kosinix.github.io/raster/out/test_resize_fill_2.jpg)
 ///
 /// ### Resize to Exact Width
 /// ```
@@ -469,11 +1272,430 @@ pub enum ResizeMode {
 /// ![](https://kosinix.github.io/raster/out/test_resize_exact_1.jpg) ![](https://kosinix.github.io/raster/out/test_resize_exact_2.jpg)
 ///
 pub fn resize(src: &mut Image, w: i32, h: i32, mode: ResizeMode) -> RasterResult<()> {
-    match mode {
-        ResizeMode::Exact => transform::resize_exact(src, w, h),
-        ResizeMode::ExactWidth => transform::resize_exact_width(src, w),
-        ResizeMode::ExactHeight => transform::resize_exact_height(src, h),
-        ResizeMode::Fit => transform::resize_fit(src, w, h),
-        ResizeMode::Fill => transform::resize_fill(src, w, h),
+    resize_with(src, w, h, mode, InterpolationMode::Bicubic)
+}
+
+/// Get the dimensions that an image of the given width and height is resampled to when resizing
+/// to w and h with a resize mode.
+///
+/// For `ResizeMode::Fill` this is the size before the excess parts are cropped, so it can be
+/// larger than w and h.
+///
+/// # Examples
+/// ```
+/// use raster::{editor, ResizeMode};
+///
+/// assert_eq!((200, 100), editor::resize_dimensions(400, 200, 200, 200, &ResizeMode::Fit));
+/// assert_eq!((400, 200), editor::resize_dimensions(400, 200, 200, 200, &ResizeMode::Fill));
+/// ```
+///
+pub fn resize_dimensions(width: i32, height: i32, w: i32, h: i32, mode: &ResizeMode) -> (i32, i32) {
+    let ratio = width as f32 / height as f32;
+
+    match *mode {
+        ResizeMode::Exact => (w, h),
+        ResizeMode::ExactWidth => (w, (w as f32 / ratio).round() as i32),
+        ResizeMode::ExactHeight => ((h as f32 * ratio) as i32, h),
+        ResizeMode::Fit => {
+            let ratio: f64 = width as f64 / height as f64;
+
+            // Try basing it on width first
+            let resize_width = w;
+            let resize_height = (w as f64 / ratio).round() as i32;
+
+            if (resize_width > w) || (resize_height > h) {
+                // Oops, either width or height does not fit
+                // So base on height instead
+                ((h as f64 * ratio).round() as i32, h)
+            } else {
+                (resize_width, resize_height)
+            }
+        }
+        ResizeMode::Fill => {
+            // Base optimum size on new width
+            let optimum_width = w;
+            let optimum_height = (w as f32 / ratio).round() as i32;
+
+            if (optimum_width < w) || (optimum_height < h) {
+                // Oops, where trying to fill and there are blank areas
+                // So base optimum size on height instead
+                ((h as f32 * ratio) as i32, h)
+            } else {
+                (optimum_width, optimum_height)
+            }
+        }
+    }
+}
+
+/// Resize an image by a scale factor, keeping its aspect ratio. 0.5 halves the size and 2.0
+/// doubles it. The new dimensions are rounded and never less than 1 pixel.
+///
+/// # Errors
+///
+/// A factor of 0 or less fails with `RasterError::InvalidScale`.
+///
+/// # Examples
+/// ```
+/// use raster::editor;
+///
+/// let mut image = raster::open("tests/in/sample.png").unwrap();
+/// editor::resize_scale(&mut image, 0.5).unwrap();
+/// assert_eq!(250, image.width);
+/// raster::save(&image, "tests/out/test_resize_scale.png").unwrap();
+/// ```
+///
+pub fn resize_scale(src: &mut Image, factor: f32) -> RasterResult<()> {
+    if factor <= 0.0 || factor.is_nan() {
+        return Err(RasterError::InvalidScale(factor));
+    }
+
+    let w = cmp::max(1, (src.width as f32 * factor).round() as i32);
+    let h = cmp::max(1, (src.height as f32 * factor).round() as i32);
+    resize(src, w, h, ResizeMode::Exact)
+}
+
+/// Resize an image to a given width, height and mode using a given interpolation.
+///
+/// `resize` uses `InterpolationMode::Bicubic`, which currently samples the same way as
+/// `InterpolationMode::Bilinear`. `InterpolationMode::Nearest` copies the closest
+/// source pixel, which is fast but looks blocky when upscaling. `InterpolationMode::Bilinear`
+/// blends the four surrounding source pixels for smoother results.
+///
+/// # Examples
+/// ```
+/// use raster::{editor, InterpolationMode, ResizeMode};
+///
+/// let mut image = raster::open("tests/in/sample.png").unwrap();
+/// let nearest = InterpolationMode::Nearest;
+/// editor::resize_with(&mut image, 200, 200, ResizeMode::Fit, nearest).unwrap();
+/// raster::save(&image, "tests/out/test_resize_with_nearest.png").unwrap();
+/// ```
+///
+pub fn resize_with(
+    src: &mut Image,
+    w: i32,
+    h: i32,
+    mode: ResizeMode,
+    interpolation: InterpolationMode,
+) -> RasterResult<()> {
+    let (resize_width, resize_height) = resize_dimensions(src.width, src.height, w, h, &mode);
+    resample(src, resize_width, resize_height, interpolation)?;
+
+    if mode == ResizeMode::Fill {
+        crop(src, w, h, PositionMode::Center, 0, 0)?; // Trim excess parts
+    }
+
+    Ok(())
+}
+
+/// Round the corners of an image by making the pixels outside a quarter circle of the given
+/// radius transparent. Pixels on the edge of the circle are partially transparent for smooth,
+/// anti-aliased corners.
+///
+/// Images are always stored as RGBA, so opaque sources like JPEG simply gain transparent corners.
+/// Save the result as PNG to keep them. The radius is limited to half the width or height. A
+/// radius of 0 or less leaves the image unchanged.
+///
+/// # Examples
+/// ```
+/// use raster::editor;
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// editor::round_corners(&mut image, 40).unwrap();
+/// raster::save(&image, "tests/out/test_round_corners.png").unwrap();
+/// ```
+///
+pub fn round_corners(src: &mut Image, radius: i32) -> RasterResult<()> {
+    let radius = cmp::min(radius, cmp::min(src.width, src.height) / 2);
+    let r = radius as f32;
+
+    for y in 0..radius {
+        for x in 0..radius {
+            // Coverage of the pixel center by the circle, 0.0 - 1.0
+            let dx = r - (x as f32 + 0.5);
+            let dy = r - (y as f32 + 0.5);
+            let coverage = (r - (dx * dx + dy * dy).sqrt() + 0.5).clamp(0.0, 1.0);
+            if coverage >= 1.0 {
+                continue;
+            }
+
+            // Mirror into the other three corners
+            let right = src.width - 1 - x;
+            let bottom = src.height - 1 - y;
+            for &(px, py) in &[(x, y), (right, y), (x, bottom), (right, bottom)] {
+                let mut p = src.get_pixel(px, py)?;
+                p.a = (p.a as f32 * coverage).round() as u8;
+                src.set_pixel(px, py, &p)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Shrink an image to fit within max_width and max_height, keeping its aspect ratio.
+///
+/// Unlike `ResizeMode::Fit`, images that already fit are left unchanged instead of being scaled
+/// up.
+///
+/// # Examples
+/// ```
+/// use raster::editor;
+///
+/// let mut image = raster::open("tests/in/sample.png").unwrap();
+/// editor::thumbnail(&mut image, 100, 100).unwrap();
+/// assert_eq!((100, 50), (image.width, image.height));
+/// raster::save(&image, "tests/out/test_thumbnail.png").unwrap();
+/// ```
+///
+pub fn thumbnail(src: &mut Image, max_width: i32, max_height: i32) -> RasterResult<()> {
+    if src.width <= max_width && src.height <= max_height {
+        return Ok(());
+    }
+
+    resize(src, max_width, max_height, ResizeMode::Fit)
+}
+
+/// Trim the margins of an image, cropping it to the content that differs from the background.
+///
+/// A pixel is background when its channels, including alpha, are all within the tolerance of the
+/// background color. If the whole image is background, it is trimmed down to its 1x1 top left
+/// pixel rather than failing.
+///
+/// # Examples
+/// ```
+/// use raster::{editor, Color};
+///
+/// let mut image = raster::open("tests/in/bnw.png").unwrap();
+/// editor::trim(&mut image, Color::white(), 10).unwrap();
+/// raster::save(&image, "tests/out/test_trim.png").unwrap();
+/// ```
+///
+pub fn trim(src: &mut Image, background: Color, tolerance: u8) -> RasterResult<()> {
+    // Bounding box of the content as left, top, right and bottom, inclusive
+    let mut bounds: Option<(i32, i32, i32, i32)> = None;
+    for y in 0..src.height {
+        for x in 0..src.width {
+            if channel_distance(&src.get_pixel(x, y)?, &background, true) <= tolerance {
+                continue;
+            }
+            bounds = Some(match bounds {
+                Some((left, top, right, bottom)) => (
+                    cmp::min(left, x),
+                    cmp::min(top, y),
+                    cmp::max(right, x),
+                    cmp::max(bottom, y),
+                ),
+                None => (x, y, x, y),
+            });
+        }
+    }
+
+    let (left, top, right, bottom) = bounds.unwrap_or((0, 0, 0, 0));
+    crop(
+        src,
+        right - left + 1,
+        bottom - top + 1,
+        PositionMode::TopLeft,
+        left,
+        top,
+    )
+}
+
+/// Blend a watermark onto an image using the normal blend mode, keeping a margin in pixels from
+/// the edges.
+///
+/// The position is worked out within the image shrunk by the margin on every side, so
+/// `PositionMode::BottomRight` with a margin of 20 leaves 20 pixels below and to the right of the
+/// mark. `PositionMode::Absolute` coordinates are measured from the margin. Opacity is any value
+/// from 0.0 - 1.0.
+///
+/// # Errors
+///
+/// A negative margin, or one larger than half the width or height of the image, fails with
+/// `RasterError::InvalidPadding`. If the mark falls outside the image, this fails with
+/// `RasterError::BlendingImageFallsOutsideCanvas`.
+///
+/// # Examples
+/// ```
+/// use raster::{editor, PositionMode};
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// let mark = raster::open("tests/in/watermark.png").unwrap();
+/// editor::watermark(&mut image, &mark, PositionMode::BottomRight, 0.5, 20).unwrap();
+/// raster::save(&image, "tests/out/test_watermark.jpg").unwrap();
+/// ```
+///
+/// ![](https://kosinix.github.io/raster/out/test_watermark.jpg)
+///
+pub fn watermark(
+    base: &mut Image,
+    mark: &Image,
+    position: PositionMode,
+    opacity: f32,
+    margin: i32,
+) -> RasterResult<()> {
+    if margin < 0 || margin * 2 > base.width || margin * 2 > base.height {
+        return Err(RasterError::InvalidPadding(margin));
+    }
+
+    let positioner = Position::new(position, margin, margin);
+    let (x, y) = positioner.get_x_y(
+        base.width - margin * 2,
+        base.height - margin * 2,
+        mark.width,
+        mark.height,
+    )?;
+
+    *base = blend(
+        base,
+        mark,
+        BlendMode::Normal,
+        opacity,
+        PositionMode::Absolute(x, y),
+        0,
+        0,
+    )?;
+
+    Ok(())
+}
+
+// Private functions
+
+// Returns the largest difference between the channels of two colors.
+fn channel_distance(a: &Color, b: &Color, alpha: bool) -> u8 {
+    let distance = cmp::max(
+        cmp::max(a.r.abs_diff(b.r), a.g.abs_diff(b.g)),
+        a.b.abs_diff(b.b),
+    );
+    if alpha {
+        cmp::max(distance, a.a.abs_diff(b.a))
+    } else {
+        distance
+    }
+}
+
+// Clip the line from x0, y0 to x1, y1 to a width x height image using the Liang-Barsky
+// algorithm. Returns None when the line misses the image.
+fn clip_line(
+    width: i32,
+    height: i32,
+    x0: i32,
+    y0: i32,
+    x1: i32,
+    y1: i32,
+) -> Option<(i32, i32, i32, i32)> {
+    let (x0, y0) = (x0 as f64, y0 as f64);
+    let (dx, dy) = (x1 as f64 - x0, y1 as f64 - y0);
+    let (right, bottom) = ((width - 1) as f64, (height - 1) as f64);
+
+    // The line is p0 + t * d for t in 0.0 - 1.0. Each edge of the image limits the range of t.
+    let (mut t0, mut t1) = (0.0f64, 1.0f64);
+    for &(p, q) in &[(-dx, x0), (dx, right - x0), (-dy, y0), (dy, bottom - y0)] {
+        if p == 0.0 {
+            // Parallel to the edge, and outside of it
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+    if t0 > t1 {
+        return None;
+    }
+
+    Some((
+        (x0 + t0 * dx).round() as i32,
+        (y0 + t0 * dy).round() as i32,
+        (x0 + t1 * dx).round() as i32,
+        (y0 + t1 * dy).round() as i32,
+    ))
+}
+
+// Compute the x, y, width and height of a crop region, clipped to the source image.
+fn crop_region(
+    src: &Image,
+    crop_width: i32,
+    crop_height: i32,
+    position: PositionMode,
+    offset_x: i32,
+    offset_y: i32,
+) -> RasterResult<(i32, i32, i32, i32)> {
+    // Turn into positioner struct
+    let positioner = Position::new(position, offset_x, offset_y);
+
+    let (offset_x, offset_y) = positioner.get_x_y(src.width, src.height, crop_width, crop_height)?;
+    let offset_x = cmp::max(0, offset_x);
+    let offset_y = cmp::max(0, offset_y);
+
+    let height2 = {
+        let height2 = offset_y + crop_height;
+        cmp::min(height2, src.height)
+    };
+
+    let width2 = {
+        let width2 = offset_x + crop_width;
+        cmp::min(width2, src.width)
+    };
+
+    Ok((
+        offset_x,
+        offset_y,
+        cmp::max(0, width2 - offset_x),
+        cmp::max(0, height2 - offset_y),
+    ))
+}
+
+// Draw a single pixel, skipping it if it falls outside the image. Colors with alpha are blended.
+fn draw_pixel(src: &mut Image, x: i32, y: i32, color: &Color) -> RasterResult<()> {
+    if x < 0 || y < 0 || x >= src.width || y >= src.height {
+        return Ok(());
+    }
+    if color.a == 255 {
+        return src.set_pixel(x, y, color);
+    }
+
+    let base = src.get_pixel(x, y)?;
+    src.set_pixel(x, y, &blend::normal_color(&base, color, 1.0))
+}
+
+// The largest n >= 0 with n * n + b * n < c, or -1 when there is none. b is 0 or -1.
+fn largest_root(b: i64, c: i64) -> i64 {
+    let mut n = (cmp::max(c, 0) as f64).sqrt() as i64 + 2;
+    while n >= 0 && n * n + b * n >= c {
+        n -= 1;
+    }
+    n
+}
+
+// Visit the pixels of a line from x0, y0 to x1, y1 using Bresenham's algorithm.
+fn walk_line<F>(x0: i32, y0: i32, x1: i32, y1: i32, mut visit: F) -> RasterResult<()>
+where
+    F: FnMut(i32, i32) -> RasterResult<()>,
+{
+    let dx = (x1 as i64 - x0 as i64).abs();
+    let dy = -(y1 as i64 - y0 as i64).abs();
+    let step_x = if x0 < x1 { 1 } else { -1 };
+    let step_y = if y0 < y1 { 1 } else { -1 };
+    let mut error = dx + dy;
+    let (mut x, mut y) = (x0, y0);
+
+    loop {
+        visit(x, y)?;
+        if x == x1 && y == y1 {
+            return Ok(());
+        }
+        let error2 = error * 2;
+        if error2 >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if error2 <= dx {
+            error += dx;
+            y += step_y;
+        }
     }
 }
@@ -1,12 +1,13 @@
 //!  A module for encoding/decoding.
 
 // from rust
-use std::path::Path;
-use std::fs::File;
-use std::io::BufWriter;
+use std::cmp;
+use std::io::{Read, Write};
 
 // from external crate
+use deflate;
 use gif;
+use piston_image;
 use png;
 
 // from local crate
@@ -14,9 +15,66 @@ use error::{RasterError, RasterResult};
 use Image;
 use ImageFormat;
 
+/// Compression levels for saving PNG images. Better compression makes smaller files but takes
+/// longer to save. The pixels are the same for every level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PngCompression {
+    /// Fast compression, used by `raster::save`.
+    Fast,
+    /// A balance between file size and speed.
+    Default,
+    /// The smallest files.
+    Best,
+}
+
+// Decode in the given format
+pub fn decode<R: Read>(r: R, format: ImageFormat) -> RasterResult<Image> {
+    match format {
+        ImageFormat::Bmp => decode_bmp(r),
+        ImageFormat::Gif => decode_gif(r),
+        ImageFormat::Jpeg => decode_jpeg(r),
+        ImageFormat::Png => decode_png(r),
+        ImageFormat::Webp => decode_webp(r),
+    }
+}
+
+// Detect the format from the magic bytes at the start of the data
+pub fn detect_format(data: &[u8]) -> RasterResult<ImageFormat> {
+    if data.starts_with(b"BM") {
+        Ok(ImageFormat::Bmp)
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Ok(ImageFormat::Gif)
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Ok(ImageFormat::Jpeg)
+    } else if data.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Ok(ImageFormat::Png)
+    } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        Ok(ImageFormat::Webp)
+    } else {
+        Err(RasterError::UnsupportedFormat("unknown".to_string()))
+    }
+}
+
+// Decode BMP
+pub fn decode_bmp<R: Read>(r: R) -> RasterResult<Image> {
+    decode_piston(r, piston_image::ImageFormat::Bmp, ImageFormat::Bmp)
+}
+
+// Encode BMP as 32 bits per pixel, which keeps the alpha channel.
+pub fn encode_bmp<W: Write>(image: &Image, mut w: W) -> RasterResult<()> {
+    piston_image::codecs::bmp::BmpEncoder::new(&mut w)
+        .encode(
+            &image.bytes,
+            image.width as u32,
+            image.height as u32,
+            piston_image::ColorType::Rgba8,
+        )
+        .map_err(|_| RasterError::Encode(ImageFormat::Bmp, "Format".to_string()))
+}
+
 // Decode GIF
-pub fn decode_gif(image_file: &File) -> RasterResult<Image> {
-    let mut decoder = gif::Decoder::new(image_file);
+pub fn decode_gif<R: Read>(r: R) -> RasterResult<Image> {
+    let mut decoder = gif::Decoder::new(r);
 
     // Configure the decoder such that it will expand the image to RGBA.
     gif::SetParameter::set(&mut decoder, gif::ColorOutput::RGBA);
@@ -42,24 +100,143 @@ pub fn decode_gif(image_file: &File) -> RasterResult<Image> {
     }
 }
 
+// Decode all frames of a GIF, each composited onto the full canvas
+pub fn decode_gif_frames<R: Read>(r: R) -> RasterResult<Vec<Image>> {
+    let mut decoder = gif::Decoder::new(r);
+
+    // Configure the decoder such that it will expand the image to RGBA.
+    gif::SetParameter::set(&mut decoder, gif::ColorOutput::RGBA);
+
+    // Read the file header
+    let mut reader = decoder.read_info()?;
+    let w = reader.width() as usize;
+    let h = reader.height() as usize;
+
+    let mut canvas = vec![0; w * h * 4];
+    let mut frames = Vec::new();
+    while let Some(frame) = reader.read_next_frame()? {
+        let previous = match frame.dispose {
+            gif::DisposalMethod::Previous => Some(canvas.clone()),
+            _ => None,
+        };
+
+        // Draw the opaque pixels of the frame that fall inside the canvas
+        let left = frame.left as usize;
+        let top = frame.top as usize;
+        let frame_w = frame.width as usize;
+        let frame_h = frame.height as usize;
+        for y in 0..frame_h {
+            for x in 0..frame_w {
+                let from = (y * frame_w + x) * 4;
+                if left + x >= w || top + y >= h || frame.buffer[from + 3] == 0 {
+                    continue;
+                }
+                let to = ((top + y) * w + left + x) * 4;
+                canvas[to..to + 4].copy_from_slice(&frame.buffer[from..from + 4]);
+            }
+        }
+
+        frames.push(Image {
+            width: w as i32,
+            height: h as i32,
+            bytes: canvas.clone(),
+        });
+
+        // Prepare the canvas for the next frame
+        match frame.dispose {
+            gif::DisposalMethod::Background => {
+                for y in top..cmp::min(top + frame_h, h) {
+                    let start = (y * w + left) * 4;
+                    let end = (y * w + cmp::min(left + frame_w, w)) * 4;
+                    for byte in &mut canvas[start..end] {
+                        *byte = 0;
+                    }
+                }
+            }
+            gif::DisposalMethod::Previous => {
+                if let Some(previous) = previous {
+                    canvas = previous;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if frames.is_empty() {
+        return Err(RasterError::Decode(
+            ImageFormat::Gif,
+            "Error getting frame info".to_string(),
+        ));
+    }
+
+    Ok(frames)
+}
+
+// Encode in the given format
+pub fn encode<W: Write>(image: &Image, w: W, format: ImageFormat, quality: u8) -> RasterResult<()> {
+    match format {
+        ImageFormat::Bmp => encode_bmp(image, w),
+        ImageFormat::Gif => encode_gif(image, w),
+        ImageFormat::Jpeg => encode_jpeg(image, w, quality),
+        ImageFormat::Png => encode_png(image, w),
+        ImageFormat::Webp => Err(RasterError::UnsupportedFormat("webp".to_string())),
+    }
+}
+
 // Encode GIF
-pub fn encode_gif(image: &Image, path: &Path) -> RasterResult<()> {
-    // Open the file with basic error check
-    let file = File::create(path)?;
-    let writer = BufWriter::new(file);
+pub fn encode_gif<W: Write>(image: &Image, w: W) -> RasterResult<()> {
     let frame = gif::Frame::from_rgba(
         image.width as u16,
         image.height as u16,
         &mut image.bytes.clone(),
     ); // TODO: Perf issue?
-    let mut encoder = gif::Encoder::new(writer, frame.width, frame.height, &[])?;
+    let mut encoder = gif::Encoder::new(w, frame.width, frame.height, &[])?;
     encoder.write_frame(&frame).map_err(RasterError::Io)?;
     Ok(())
 }
 
+// Encode the frames of an animated GIF that loops forever. Delay is in 1/100th of a second.
+pub fn encode_gif_frames<W: Write>(frames: &[Image], w: W, delay: u16) -> RasterResult<()> {
+    let first = match frames.first() {
+        Some(first) => first,
+        None => return Err(RasterError::Encode(ImageFormat::Gif, "No frames".to_string())),
+    };
+
+    let mut encoder = gif::Encoder::new(w, first.width as u16, first.height as u16, &[])?;
+    gif::SetParameter::set(&mut encoder, gif::Repeat::Infinite)?;
+    for image in frames {
+        let mut frame = gif::Frame::from_rgba(
+            image.width as u16,
+            image.height as u16,
+            &mut image.bytes.clone(),
+        );
+        frame.delay = delay;
+        frame.dispose = gif::DisposalMethod::Background;
+        encoder.write_frame(&frame).map_err(RasterError::Io)?;
+    }
+    Ok(())
+}
+
+// Encode JPEG
+pub fn encode_jpeg<W: Write>(image: &Image, mut w: W, quality: u8) -> RasterResult<()> {
+    piston_image::codecs::jpeg::JpegEncoder::new_with_quality(&mut w, quality)
+        .encode(
+            &image.bytes,
+            image.width as u32,
+            image.height as u32,
+            piston_image::ColorType::Rgba8,
+        )
+        .map_err(|_| RasterError::Encode(ImageFormat::Jpeg, "Format".to_string()))
+}
+
+// Decode JPEG
+pub fn decode_jpeg<R: Read>(r: R) -> RasterResult<Image> {
+    decode_piston(r, piston_image::ImageFormat::Jpeg, ImageFormat::Jpeg)
+}
+
 // Decode PNG
-pub fn decode_png(image_file: &File) -> RasterResult<Image> {
-    let decoder = png::Decoder::new(image_file);
+pub fn decode_png<R: Read>(r: R) -> RasterResult<Image> {
+    let decoder = png::Decoder::new(r);
     let (info, mut reader) = decoder.read_info()?;
     let mut bytes = vec![0; info.buffer_size()];
 
@@ -84,15 +261,142 @@ pub fn decode_png(image_file: &File) -> RasterResult<Image> {
     })
 }
 
+// Decode PNG row by row while shrinking it to the dimensions returned by target, which gets the
+// source width and height. Each output pixel is the average of the source pixels that map to it,
+// so only one source row and one row of sums are kept in memory. Returns None without decoding
+// the pixels when the image can't be streamed: it is interlaced or the target is not smaller.
+pub fn decode_png_downscaled<R, F>(r: R, target: F) -> RasterResult<Option<Image>>
+where
+    R: Read,
+    F: FnOnce(i32, i32) -> (i32, i32),
+{
+    let decoder = png::Decoder::new(r);
+    let (info, mut reader) = decoder.read_info()?;
+    let (width, height) = (info.width as usize, info.height as usize);
+    let (w, h) = target(width as i32, height as i32);
+    if reader.info().interlaced || w < 1 || h < 1 || w as usize > width || h as usize > height {
+        return Ok(None);
+    }
+    let (w, h) = (w as usize, h as usize);
+    let samples = reader.output_color_type().0.samples();
+
+    // Output column of every source column and the number of source columns per output column
+    let columns: Vec<usize> = (0..width).map(|x| x * w / width).collect();
+    let mut column_counts = vec![0u64; w];
+    for &x in &columns {
+        column_counts[x] += 1;
+    }
+
+    let mut bytes = Vec::with_capacity(w * h * 4);
+    let mut sums = vec![0u64; w * 4];
+    let mut row_count = 0;
+    for y in 0..height {
+        let row = match reader.next_row()? {
+            Some(row) => row,
+            None => return Err(RasterError::Decode(ImageFormat::Png, "Rows".to_string())),
+        };
+        for (pixel, &x) in row.chunks(samples).zip(&columns) {
+            let rgba = match samples {
+                1 => [pixel[0], pixel[0], pixel[0], 255],
+                2 => [pixel[0], pixel[0], pixel[0], pixel[1]],
+                3 => [pixel[0], pixel[1], pixel[2], 255],
+                _ => [pixel[0], pixel[1], pixel[2], pixel[3]],
+            };
+            for (sum, &value) in sums[x * 4..x * 4 + 4].iter_mut().zip(&rgba) {
+                *sum += value as u64;
+            }
+        }
+        row_count += 1;
+
+        // Write out the row of averages once the next source row belongs to another output row
+        if y + 1 == height || (y + 1) * h / height != y * h / height {
+            for (x, pixel) in sums.chunks_mut(4).enumerate() {
+                let count = column_counts[x] * row_count;
+                for sum in pixel.iter_mut() {
+                    bytes.push(((*sum + count / 2) / count) as u8);
+                    *sum = 0;
+                }
+            }
+            row_count = 0;
+        }
+    }
+
+    Ok(Some(Image {
+        width: w as i32,
+        height: h as i32,
+        bytes,
+    }))
+}
+
 // Encode PNG
-pub fn encode_png(image: &Image, path: &Path) -> RasterResult<()> {
-    // Open the file with basic error check
-    let file = File::create(path)?;
-    let ref mut w = BufWriter::new(file);
+pub fn encode_png<W: Write>(image: &Image, w: W) -> RasterResult<()> {
+    encode_png_with(image, w, PngCompression::Fast)
+}
+
+// Encode PNG with a compression level. The png crate always compresses fast, so the image data
+// is filtered and compressed here and written as a single IDAT chunk.
+pub fn encode_png_with<W: Write>(
+    image: &Image,
+    w: W,
+    compression: PngCompression,
+) -> RasterResult<()> {
+    let level = match compression {
+        PngCompression::Fast => deflate::Compression::Fast,
+        PngCompression::Default => deflate::Compression::Default,
+        PngCompression::Best => deflate::Compression::Best,
+    };
+
+    let row_len = image.width as usize * 4;
+    if image.bytes.is_empty() || image.bytes.len() != row_len * image.height as usize {
+        return Err(RasterError::Encode(ImageFormat::Png, "Format".to_string()));
+    }
+
+    // Each row starts with its filter type, Sub, followed by the difference of each byte with the
+    // byte of the pixel to the left
+    let mut zlib = deflate::write::ZlibEncoder::new(Vec::new(), level);
+    let mut filtered = vec![0; row_len + 1];
+    filtered[0] = 1;
+    for row in image.bytes.chunks(row_len) {
+        filtered[1..5].copy_from_slice(&row[0..4]);
+        for i in 4..row_len {
+            filtered[i + 1] = row[i].wrapping_sub(row[i - 4]);
+        }
+        zlib.write_all(&filtered)?;
+    }
+    let data = zlib.finish()?;
 
     let mut encoder = png::Encoder::new(w, image.width as u32, image.height as u32);
     png::HasParameters::set(&mut encoder, png::ColorType::RGBA);
     png::HasParameters::set(&mut encoder, png::BitDepth::Eight);
     let mut writer = encoder.write_header()?;
-    Ok(writer.write_image_data(&image.bytes)?)
+    Ok(writer.write_chunk(*b"IDAT", &data)?)
+}
+
+// Decode WebP
+pub fn decode_webp<R: Read>(r: R) -> RasterResult<Image> {
+    decode_piston(r, piston_image::ImageFormat::WebP, ImageFormat::Webp)
+}
+
+// Private functions
+
+// Decode using the image crate, reporting decode errors under the given format
+fn decode_piston<R: Read>(
+    mut r: R,
+    piston_format: piston_image::ImageFormat,
+    format: ImageFormat,
+) -> RasterResult<Image> {
+    let mut data = Vec::new();
+    r.read_to_end(&mut data)?;
+    let src = piston_image::load_from_memory_with_format(&data, piston_format).map_err(|err| {
+        match RasterError::from(err) {
+            RasterError::Decode(_, msg) => RasterError::Decode(format, msg),
+            err => err,
+        }
+    })?;
+    let rgba = src.to_rgba8();
+    Ok(Image {
+        width: rgba.width() as i32,
+        height: rgba.height() as i32,
+        bytes: rgba.into_raw(),
+    })
 }
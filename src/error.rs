@@ -1,6 +1,8 @@
 //!  A module for error types.
 
 // from rust
+use std::error::Error;
+use std::fmt;
 use std::io::Error as IoError;
 use std::num::ParseIntError;
 
@@ -25,10 +27,46 @@ pub enum RasterError {
     InvalidHex,
     /// Error parsing a hex string.
     HexParse(ParseIntError),
-    /// Blending error.
-    BlendingImageFallsOutsideCanvas,
+    /// The top image of a blend does not overlap the canvas. Holds the computed x and y of the
+    /// top image and the dimensions of the canvas and the top image.
+    BlendingImageFallsOutsideCanvas {
+        offset: (i32, i32),
+        canvas: (i32, i32),
+        image: (i32, i32),
+    },
+    /// Rotation angle that is not a finite number.
+    InvalidAngle(f32),
+    /// Unknown blend mode name.
+    InvalidBlendMode(String),
+    /// Unknown color channel name.
+    InvalidChannel(String),
     /// Invalid gamma parameter.
     InvalidGamma(f32),
+    /// Kernel length is not the square of its width or the width is even.
+    InvalidKernel(usize, usize),
+    /// Transformation matrix that can't be inverted.
+    InvalidMatrix,
+    /// Negative padding or border thickness, or a watermark margin that doesn't fit the image.
+    InvalidPadding(i32),
+    /// Number of palette colors is 0.
+    InvalidPaletteSize(usize),
+    /// Polygon with fewer than 3 points.
+    InvalidPolygon(usize),
+    /// Invalid number of posterize levels.
+    InvalidPosterizeLevels(u8),
+    /// Unknown resize mode name.
+    InvalidResizeMode(String),
+    /// Scale factor that is not greater than 0.
+    InvalidScale(f32),
+    /// Unknown transform mode name.
+    InvalidTransformMode(String),
+    /// Images that should have the same dimensions don't.
+    DimensionMismatch {
+        expected: (i32, i32),
+        found: (i32, i32),
+    },
+    /// Raw pixel buffer length does not match the image dimensions.
+    BufferSizeMismatch { expected: usize, found: usize },
     /// Error during decoding.
     Decode(ImageFormat, String),
     /// Error during encoding.
@@ -39,6 +77,92 @@ pub enum RasterError {
     Unexpected,
 }
 
+/// Describe the error in a sentence, eg. for showing it to users.
+impl fmt::Display for RasterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RasterError::Io(ref err) => write!(f, "I/O error: {}", err),
+            RasterError::PixelOutOfBounds(x, y) => {
+                write!(f, "pixel {}, {} is outside the image", x, y)
+            }
+            RasterError::InvalidStartIndex(index) => write!(f, "invalid start index {}", index),
+            RasterError::InvalidHex => write!(f, "invalid hex color format"),
+            RasterError::HexParse(ref err) => write!(f, "invalid hex color: {}", err),
+            RasterError::BlendingImageFallsOutsideCanvas {
+                offset,
+                canvas,
+                image,
+            } => write!(
+                f,
+                "blended {}x{} image at {}, {} falls outside the {}x{} canvas",
+                image.0, image.1, offset.0, offset.1, canvas.0, canvas.1
+            ),
+            RasterError::InvalidAngle(angle) => write!(f, "angle {} is not a finite number", angle),
+            RasterError::InvalidBlendMode(ref name) => write!(f, "unknown blend mode {:?}", name),
+            RasterError::InvalidChannel(ref name) => write!(f, "unknown channel {:?}", name),
+            RasterError::InvalidGamma(gamma) => {
+                write!(f, "gamma {} is outside the 0.01 - 9.99 range", gamma)
+            }
+            RasterError::InvalidKernel(len, width) => write!(
+                f,
+                "kernel of {} values does not fit an odd width of {}",
+                len, width
+            ),
+            RasterError::InvalidMatrix => write!(f, "transformation matrix can't be inverted"),
+            RasterError::InvalidPadding(padding) => {
+                write!(f, "invalid padding or margin {}", padding)
+            }
+            RasterError::InvalidPaletteSize(size) => write!(f, "invalid palette size {}", size),
+            RasterError::InvalidPolygon(points) => {
+                write!(f, "polygon needs at least 3 points, got {}", points)
+            }
+            RasterError::InvalidPosterizeLevels(levels) => {
+                write!(f, "posterize levels {} is below 2", levels)
+            }
+            RasterError::InvalidResizeMode(ref name) => {
+                write!(f, "unknown resize mode {:?}", name)
+            }
+            RasterError::InvalidScale(scale) => {
+                write!(f, "scale factor {} is not greater than 0", scale)
+            }
+            RasterError::InvalidTransformMode(ref name) => {
+                write!(f, "unknown transform mode {:?}", name)
+            }
+            RasterError::DimensionMismatch { expected, found } => write!(
+                f,
+                "expected a {}x{} image, found {}x{}",
+                expected.0, expected.1, found.0, found.1
+            ),
+            RasterError::BufferSizeMismatch { expected, found } => write!(
+                f,
+                "expected a buffer of {} bytes, found {}",
+                expected, found
+            ),
+            RasterError::Decode(ref format, ref msg) => {
+                write!(f, "error decoding {:?}: {}", format, msg)
+            }
+            RasterError::Encode(ref format, ref msg) => {
+                write!(f, "error encoding {:?}: {}", format, msg)
+            }
+            RasterError::UnsupportedFormat(ref format) => {
+                write!(f, "unsupported format {:?}", format)
+            }
+            RasterError::Unexpected => write!(f, "unexpected error"),
+        }
+    }
+}
+
+/// Lets RasterError be boxed into Box<dyn Error> and used with the `?` operator.
+impl Error for RasterError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            RasterError::Io(ref err) => Some(err),
+            RasterError::HexParse(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 /// Convert std::io::Error to RasterError::Io
 impl From<IoError> for RasterError {
     fn from(err: IoError) -> RasterError {
@@ -70,25 +194,8 @@ impl From<gif::DecodingError> for RasterError {
 impl From<piston_image::ImageError> for RasterError {
     fn from(err: piston_image::ImageError) -> RasterError {
         match err {
-            piston_image::ImageError::FormatError(msg) => {
-                RasterError::Decode(ImageFormat::Jpeg, msg)
-            }
-            piston_image::ImageError::DimensionError => {
-                RasterError::Decode(ImageFormat::Jpeg, "DimensionError".to_string())
-            }
-            piston_image::ImageError::UnsupportedError(msg) => {
-                RasterError::Decode(ImageFormat::Jpeg, msg)
-            }
-            piston_image::ImageError::UnsupportedColor(_) => {
-                RasterError::Decode(ImageFormat::Jpeg, "UnsupportedColor".to_string())
-            }
-            piston_image::ImageError::NotEnoughData => {
-                RasterError::Decode(ImageFormat::Jpeg, "NotEnoughData".to_string())
-            }
             piston_image::ImageError::IoError(io_err) => RasterError::Io(io_err),
-            piston_image::ImageError::ImageEnd => {
-                RasterError::Decode(ImageFormat::Jpeg, "ImageEnd".to_string())
-            }
+            err => RasterError::Decode(ImageFormat::Jpeg, err.to_string()),
         }
     }
 }
@@ -0,0 +1,181 @@
+//!  A module for reading EXIF metadata from JPEG files.
+
+// from rust
+
+// from external crate
+
+// from local crate
+use error::RasterResult;
+use transform::{self, TransformMode};
+use Image;
+
+const MAKE: u16 = 0x010F;
+const MODEL: u16 = 0x0110;
+const ORIENTATION: u16 = 0x0112;
+const DATETIME: u16 = 0x0132;
+const EXIF_IFD: u16 = 0x8769;
+const EXPOSURE_TIME: u16 = 0x829A;
+
+/// Capture metadata read from the EXIF segment of a JPEG. Fields that are absent are `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+    /// Orientation of the image, 1 - 8. 1 is upright.
+    pub orientation: Option<u16>,
+    /// Date and time the image was last changed, eg. "2018:06:17 10:30:00".
+    pub datetime: Option<String>,
+    /// Camera manufacturer.
+    pub make: Option<String>,
+    /// Camera model.
+    pub model: Option<String>,
+    /// Exposure time in seconds as a fraction, eg. (1, 250).
+    pub exposure_time: Option<(u32, u32)>,
+}
+
+// Read the metadata of a JPEG. Anything that can't be parsed is left as None.
+pub fn metadata(jpeg: &[u8]) -> Metadata {
+    let mut metadata = Metadata::default();
+    let tiff = match Tiff::from_jpeg(jpeg) {
+        Some(tiff) => tiff,
+        None => return metadata,
+    };
+    let ifd0 = match tiff.u32(4) {
+        Some(ifd0) => ifd0 as usize,
+        None => return metadata,
+    };
+
+    metadata.orientation = tiff
+        .find(ifd0, ORIENTATION)
+        .and_then(|entry| tiff.u16(entry + 8))
+        .and_then(|value| match value {
+            1..=8 => Some(value),
+            _ => None,
+        });
+    metadata.datetime = tiff.find(ifd0, DATETIME).and_then(|entry| tiff.ascii(entry));
+    metadata.make = tiff.find(ifd0, MAKE).and_then(|entry| tiff.ascii(entry));
+    metadata.model = tiff.find(ifd0, MODEL).and_then(|entry| tiff.ascii(entry));
+    metadata.exposure_time = tiff
+        .find(ifd0, EXIF_IFD)
+        .and_then(|entry| tiff.u32(entry + 8))
+        .and_then(|exif_ifd| tiff.find(exif_ifd as usize, EXPOSURE_TIME))
+        .and_then(|entry| tiff.rational(entry));
+
+    metadata
+}
+
+// Flip and rotate an image so that it is displayed upright according to its orientation tag.
+pub fn orient(image: &mut Image, orientation: u16) -> RasterResult<()> {
+    match orientation {
+        2 => transform::flip(image, TransformMode::Horizontal),
+        3 => transform::rotate180(image),
+        4 => transform::flip(image, TransformMode::Vertical),
+        5 => {
+            transform::rotate90(image)?;
+            transform::flip(image, TransformMode::Horizontal)
+        }
+        6 => transform::rotate90(image),
+        7 => {
+            transform::rotate270(image)?;
+            transform::flip(image, TransformMode::Horizontal)
+        }
+        8 => transform::rotate270(image),
+        _ => Ok(()),
+    }
+}
+
+// Private functions
+
+// The TIFF structure inside an EXIF segment. Offsets are relative to the TIFF header.
+struct Tiff<'a> {
+    data: &'a [u8],
+    little_endian: bool,
+}
+
+impl<'a> Tiff<'a> {
+    // Find the EXIF segment by walking the JPEG markers up to the start of the image data.
+    fn from_jpeg(jpeg: &'a [u8]) -> Option<Tiff<'a>> {
+        if !jpeg.starts_with(&[0xFF, 0xD8]) {
+            return None;
+        }
+
+        let mut pos = 2;
+        while pos + 4 <= jpeg.len() && jpeg[pos] == 0xFF {
+            let marker = jpeg[pos + 1];
+            let len = ((jpeg[pos + 2] as usize) << 8) | jpeg[pos + 3] as usize;
+            // Start of scan, no more metadata
+            if marker == 0xDA || len < 2 {
+                return None;
+            }
+            let segment = jpeg.get(pos + 4..pos + 2 + len)?;
+            if marker == 0xE1 && segment.starts_with(b"Exif\0\0") {
+                return Tiff::new(&segment[6..]);
+            }
+            pos += 2 + len;
+        }
+
+        None
+    }
+
+    fn new(data: &'a [u8]) -> Option<Tiff<'a>> {
+        let little_endian = match data.get(0..2)? {
+            b"II" => true,
+            b"MM" => false,
+            _ => return None,
+        };
+        let tiff = Tiff {
+            data,
+            little_endian,
+        };
+        if tiff.u16(2)? == 42 {
+            Some(tiff)
+        } else {
+            None
+        }
+    }
+
+    fn u16(&self, offset: usize) -> Option<u16> {
+        let b = self.data.get(offset..offset + 2)?;
+        if self.little_endian {
+            Some((b[1] as u16) << 8 | b[0] as u16)
+        } else {
+            Some((b[0] as u16) << 8 | b[1] as u16)
+        }
+    }
+
+    fn u32(&self, offset: usize) -> Option<u32> {
+        let hi = self.u16(offset + if self.little_endian { 2 } else { 0 })?;
+        let lo = self.u16(offset + if self.little_endian { 0 } else { 2 })?;
+        Some((hi as u32) << 16 | lo as u32)
+    }
+
+    // Read an ASCII entry, dropping the trailing NUL and whitespace.
+    fn ascii(&self, entry: usize) -> Option<String> {
+        let count = self.u32(entry + 4)? as usize;
+        let start = if count <= 4 {
+            entry + 8
+        } else {
+            self.u32(entry + 8)? as usize
+        };
+        let bytes = self.data.get(start..start + count)?;
+        let text = String::from_utf8_lossy(bytes);
+        let text = text.trim_end_matches('\0').trim();
+        if text.is_empty() {
+            None
+        } else {
+            Some(text.to_string())
+        }
+    }
+
+    // Read a rational entry as a numerator and denominator.
+    fn rational(&self, entry: usize) -> Option<(u32, u32)> {
+        let start = self.u32(entry + 8)? as usize;
+        Some((self.u32(start)?, self.u32(start + 4)?))
+    }
+
+    // Returns the offset of the 12 byte entry with the given tag in an IFD.
+    fn find(&self, ifd: usize, tag: u16) -> Option<usize> {
+        let count = self.u16(ifd)? as usize;
+        (0..count)
+            .map(|i| ifd + 2 + i * 12)
+            .find(|&entry| self.u16(entry) == Some(tag))
+    }
+}
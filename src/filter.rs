@@ -1,7 +1,6 @@
 //!  A module for filtering pixels.
 
 // from rust
-use std::cmp;
 
 // from external crate
 
@@ -9,6 +8,7 @@ use std::cmp;
 use error::{RasterError, RasterResult};
 use Image;
 use Color;
+use palette;
 
 /// An enum for the various modes that can be used for blurring.
 #[derive(Debug)]
@@ -28,6 +28,39 @@ pub enum Orientation {
     Both,
 }
 
+/// Add random noise to every RGB channel, up to `amount * 255` brighter or darker.
+///
+/// The noise comes from a pseudo-random generator started from the seed, so the same seed always
+/// gives the same result. Channels are clamped to 0 - 255. Alpha is preserved.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// filter::add_noise(&mut image, 0.1, 42).unwrap();
+/// raster::save(&image, "tests/out/test_filter_add_noise.jpg").unwrap();
+/// ```
+///
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.jpg)
+///
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_add_noise.jpg)
+///
+pub fn add_noise(src: &mut Image, amount: f32, seed: u64) -> RasterResult<()> {
+    let mut state = seed;
+    for pixel in src.bytes.chunks_mut(4) {
+        for c in pixel.iter_mut().take(3) {
+            // -1.0 - 1.0
+            let noise = (splitmix64(&mut state) >> 40) as f32 / (1u64 << 23) as f32 - 1.0;
+            *c = clamp_channel(*c as f32 + noise * amount * 255.0);
+        }
+    }
+
+    Ok(())
+}
+
 /// Apply box or Gaussian blur.
 ///
 /// # Examples
@@ -70,9 +103,41 @@ pub fn blur(src: &mut Image, mode: BlurMode) -> RasterResult<()> {
     }
 }
 
+/// Apply a box blur of the given radius.
+///
+/// Each pixel, including its alpha, becomes the average of the (2 * radius + 1) square window
+/// around it. Samples outside the image are clamped to the nearest edge pixel. The blur is done
+/// in a horizontal and a vertical pass using a running sum so that larger radii are not slower.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// filter::box_blur(&mut image, 5).unwrap();
+/// raster::save(&image, "tests/out/test_filter_box_blur_radius.jpg").unwrap();
+/// ```
+///
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.jpg)
+///
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_box_blur_radius.jpg)
+///
+pub fn box_blur(src: &mut Image, radius: u32) -> RasterResult<()> {
+    if radius == 0 || src.width == 0 || src.height == 0 {
+        return Ok(());
+    }
+    box_blur_pass(src, radius as i64, true);
+    box_blur_pass(src, radius as i64, false);
+    Ok(())
+}
+
 /// Apply brightness.
 ///
-/// A brightness of < 0.0 will darken the image and brightness of > 1.0 will lighten it.
+/// The RGB channels are multiplied by the factor and clamped to 0 - 255. A factor of < 1.0 will
+/// darken the image, a factor of > 1.0 will lighten it and 1.0 leaves it unchanged. Alpha is
+/// preserved.
 ///
 /// # Examples
 /// ```
@@ -93,21 +158,54 @@ pub fn brightness(src: &mut Image, factor: f32) -> RasterResult<()> {
     let w: i32 = src.width;
     let h: i32 = src.height;
 
-    // if gamma < 0.01 || gamma > 9.99{
-    //     return Err(format!("Incorrect gamma value {}. Must be in range 0.01 - 9.99.", gamma));
-    // }
-    // let factor = 255.0 * factor;
+    for y in 0..h {
+        for x in 0..w {
+            let p = src.get_pixel(x, y)?;
+            let r = clamp_channel(p.r as f32 * factor);
+            let g = clamp_channel(p.g as f32 * factor);
+            let b = clamp_channel(p.b as f32 * factor);
+
+            src.set_pixel(x, y, &Color::rgba(r, g, b, p.a))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Apply contrast.
+///
+/// Each RGB channel is pushed away from or pulled towards the middle value of 128 using
+/// `(value - 128) * factor + 128`, clamped to 0 - 255. A factor of > 1.0 increases contrast,
+/// < 1.0 decreases it, 1.0 leaves the image unchanged and 0.0 turns it into flat gray. Alpha is
+/// preserved.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// filter::contrast(&mut image, 1.5).unwrap();
+/// raster::save(&image, "tests/out/test_filter_contrast.jpg").unwrap();
+/// ```
+///
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.jpg)
+///
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_contrast.jpg)
+///
+pub fn contrast(src: &mut Image, factor: f32) -> RasterResult<()> {
+    let w: i32 = src.width;
+    let h: i32 = src.height;
 
     for y in 0..h {
         for x in 0..w {
             let p = src.get_pixel(x, y)?;
-            let r = cmp::max(0, cmp::min(255, (p.r as f32 * factor) as i32));
-            let g = cmp::max(0, cmp::min(255, (p.g as f32 * factor) as i32));
-            let b = cmp::max(0, cmp::min(255, (p.b as f32 * factor) as i32));
-            // TODO: Should alpha be included?
-            let a = cmp::max(0, cmp::min(255, (p.a as f32 * factor) as i32));
+            let r = clamp_channel((p.r as f32 - 128.0) * factor + 128.0);
+            let g = clamp_channel((p.g as f32 - 128.0) * factor + 128.0);
+            let b = clamp_channel((p.b as f32 - 128.0) * factor + 128.0);
 
-            src.set_pixel(x, y, &Color::rgba(r as u8, g as u8, b as u8, a as u8))?;
+            src.set_pixel(x, y, &Color::rgba(r, g, b, p.a))?;
         }
     }
 
@@ -220,8 +318,150 @@ pub fn convolve(src: &mut Image, matrix: [[i32; 3]; 3], divisor: i32) -> RasterR
     Ok(())
 }
 
+/// Apply a convolution kernel of any odd width.
+///
+/// The kernel is given in row-major order and must contain `kernel_width * kernel_width` values.
+/// Each RGB channel is the weighted sum of its neighbors, divided by the divisor, plus the offset,
+/// clamped to 0 - 255. Samples outside the image are clamped to the nearest edge pixel. Alpha is
+/// preserved.
+///
+/// # Errors
+///
+/// If the width is even or doesn't match the kernel length, this fails with
+/// `RasterError::InvalidKernel`.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// let kernel = [
+///     -1.0, -1.0, -1.0,
+///     -1.0,  8.0, -1.0,
+///     -1.0, -1.0, -1.0,
+/// ];
+/// filter::convolve_kernel(&mut image, &kernel, 3, 1.0, 0.0).unwrap();
+/// raster::save(&image, "tests/out/test_filter_convolve_kernel.jpg").unwrap();
+/// ```
+///
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.jpg)
+///
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_convolve_kernel.jpg)
+///
+pub fn convolve_kernel(
+    src: &mut Image,
+    kernel: &[f32],
+    kernel_width: usize,
+    divisor: f32,
+    offset: f32,
+) -> RasterResult<()> {
+    if kernel_width % 2 != 1 || kernel.len() != kernel_width * kernel_width {
+        return Err(RasterError::InvalidKernel(kernel.len(), kernel_width));
+    }
+
+    let w = src.width as i64;
+    let h = src.height as i64;
+    let radius = (kernel_width / 2) as i64;
+    let copy = src.bytes.clone();
+
+    for y in 0..h {
+        for x in 0..w {
+            let mut sum = [0.0f32; 3];
+            for (ky, row) in (y - radius..).zip(kernel.chunks(kernel_width)) {
+                let ky = clamp_index(ky, h);
+                for (kx, weight) in (x - radius..).zip(row.iter()) {
+                    let start = (ky * w + clamp_index(kx, w)) as usize * 4;
+                    for (c, s) in sum.iter_mut().enumerate() {
+                        *s += copy[start + c] as f32 * weight;
+                    }
+                }
+            }
+
+            let start = (y * w + x) as usize * 4;
+            for (c, s) in sum.iter().enumerate() {
+                src.bytes[start + c] = clamp_channel(*s / divisor + offset);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Reduce the colors of an image to a palette of at most num_colors using Floyd-Steinberg
+/// dithering.
+///
+/// Like `quantize`, the palette is built using median cut. The difference between each pixel and
+/// its closest palette color is spread to the pixels to the right and below, so gradients become a
+/// pattern of palette colors instead of bands. Alpha is preserved.
+///
+/// # Errors
+///
+/// A num_colors of 0 fails with `RasterError::InvalidPaletteSize`.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// filter::dither(&mut image, 8).unwrap();
+/// raster::save(&image, "tests/out/test_filter_dither.png").unwrap();
+/// ```
+///
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.jpg)
+///
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_dither.png)
+///
+pub fn dither(src: &mut Image, num_colors: usize) -> RasterResult<()> {
+    if num_colors == 0 {
+        return Err(RasterError::InvalidPaletteSize(num_colors));
+    }
+    let palette: Vec<Color> = palette::median_cut(src, num_colors)
+        .into_iter()
+        .map(|(color, _)| color)
+        .collect();
+
+    let w = src.width as usize;
+    let h = src.height as usize;
+    // Wanted RGB of every pixel including the error spread from its neighbors
+    let mut wanted: Vec<f32> = src.bytes.iter().map(|&v| v as f32).collect();
+    for y in 0..h {
+        for x in 0..w {
+            let start = (y * w + x) * 4;
+            let (r, g, b) = (wanted[start], wanted[start + 1], wanted[start + 2]);
+            let color = &palette[palette::nearest(&palette, r, g, b)];
+            let error = [r - color.r as f32, g - color.g as f32, b - color.b as f32];
+            src.bytes[start] = color.r;
+            src.bytes[start + 1] = color.g;
+            src.bytes[start + 2] = color.b;
+
+            // Floyd-Steinberg: 7/16 right, 3/16 below left, 5/16 below, 1/16 below right
+            let neighbors = [(1, 0, 7.0), (-1, 1, 3.0), (0, 1, 5.0), (1, 1, 1.0)];
+            for &(dx, dy, weight) in &neighbors {
+                let nx = x as i64 + dx;
+                let ny = y + dy;
+                if nx < 0 || nx >= w as i64 || ny >= h {
+                    continue;
+                }
+                let neighbor = (ny * w + nx as usize) * 4;
+                for (c, e) in error.iter().enumerate() {
+                    wanted[neighbor + c] += e * weight / 16.0;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
 /// Apply emboss.
 ///
+/// Uses the emboss kernel `-2 -1 0 / -1 0 1 / 0 1 2` with an offset of 128, so flat areas become
+/// gray and edges are raised or sunken. Alpha is preserved.
+///
 /// # Examples
 /// ```
 /// use raster::filter;
@@ -239,12 +479,71 @@ pub fn convolve(src: &mut Image, matrix: [[i32; 3]; 3], divisor: i32) -> RasterR
 /// ![](https://kosinix.github.io/raster/out/test_filter_emboss.jpg)
 ///
 pub fn emboss(src: &mut Image) -> RasterResult<()> {
-    let matrix: [[i32; 3]; 3] = [[-2, -1, 0], [-1, 1, 1], [0, 1, 2]];
-    convolve(src, matrix, 1)
+    let kernel = [-2.0, -1.0, 0.0, -1.0, 0.0, 1.0, 0.0, 1.0, 2.0];
+    convolve_kernel(src, &kernel, 3, 1.0, 128.0)
+}
+
+/// Equalize the histogram of an image, spreading its luminance over the full 0 - 255 range to
+/// improve contrast.
+///
+/// The new luminance of each pixel comes from the cumulative luminance histogram. The RGB channels
+/// of a pixel are all shifted by the change in its luminance, rather than equalized one by one,
+/// so colors don't shift in hue. A flat image is left unchanged. Alpha is preserved.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// filter::equalize(&mut image).unwrap();
+/// raster::save(&image, "tests/out/test_filter_equalize.jpg").unwrap();
+/// ```
+///
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.jpg)
+///
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_equalize.jpg)
+///
+pub fn equalize(src: &mut Image) -> RasterResult<()> {
+    let histogram = src.histogram();
+    let total = (src.width * src.height) as u32;
+
+    // Cumulative count of the pixels at or below each luminance
+    let mut cdf = [0; 256];
+    let mut sum = 0;
+    for (l, &count) in histogram.luminance.iter().enumerate() {
+        sum += count;
+        cdf[l] = sum;
+    }
+    let cdf_min = cdf.iter().cloned().find(|&c| c > 0).unwrap_or(0);
+    if total == cdf_min {
+        return Ok(());
+    }
+
+    let mut lut = [0; 256];
+    for (l, &c) in cdf.iter().enumerate() {
+        let level = (c.saturating_sub(cdf_min)) as f32 / (total - cdf_min) as f32 * 255.0;
+        lut[l] = level.round() as i32 - l as i32;
+    }
+
+    for pixel in src.bytes.chunks_mut(4) {
+        let l = Color::rgb(pixel[0], pixel[1], pixel[2]).luminance();
+        let shift = lut[l as usize] as f32;
+        for c in pixel.iter_mut().take(3) {
+            *c = clamp_channel(*c as f32 + shift);
+        }
+    }
+
+    Ok(())
 }
 
 /// Apply Sobel edge detection.
 ///
+/// `Orientation::Both` and `Orientation::DiagonalBoth` combine the gradients of both directions
+/// as `sqrt(gx² + gy²)` on the luminance, so edges light up regardless of their direction. Their
+/// output is opaque.
+///
 /// # Examples
 /// ```
 /// use raster::{filter, Orientation};
@@ -286,29 +585,33 @@ pub fn sobel(src: &mut Image, mode: Orientation) -> RasterResult<()> {
     convolve(src, matrix, 1)
 }
 
+// Combine two signed gradients on the luminance as sqrt(gx² + gy²).
 fn sobel_both(
     src: &mut Image,
     matrix_one: [[i32; 3]; 3],
     matrix_two: [[i32; 3]; 3],
 ) -> RasterResult<()> {
-    let mut image_x = src.clone();
-    let mut image_y = src.clone();
-    convolve(&mut image_x, matrix_one, 1)?;
-    convolve(&mut image_y, matrix_two, 1)?;
+    let copy = src.clone();
 
     let w: i32 = src.width;
     let h: i32 = src.height;
     for y in 0..h {
         for x in 0..w {
-            let pixel_x = image_x.get_pixel(x, y)?;
-            let pixel_y = image_y.get_pixel(x, y)?;
+            let mut gx: i32 = 0;
+            let mut gy: i32 = 0;
+            for (m_y, src_y) in (0..3).zip(y - 1..y + 2) {
+                let src_y = clamp_index(src_y as i64, h as i64) as i32;
+                for (m_x, src_x) in (0..3).zip(x - 1..x + 2) {
+                    let src_x = clamp_index(src_x as i64, w as i64) as i32;
+                    let lum = copy.get_pixel(src_x, src_y)?.luminance() as i32;
+                    gx += lum * matrix_one[m_y][m_x];
+                    gy += lum * matrix_two[m_y][m_x];
+                }
+            }
+
             // Calculate the sum of the derivatives with sqrt((dImage/dx)²+(dImage/dy)²)
-            let pixel = ((pixel_x.r as f64).powi(2) + (pixel_y.r as f64).powi(2)).sqrt();
-            src.set_pixel(
-                x,
-                y,
-                &Color::rgba(pixel as u8, pixel as u8, pixel as u8, pixel_x.a as u8),
-            )?;
+            let v = clamp_channel(((gx * gx + gy * gy) as f32).sqrt());
+            src.set_pixel(x, y, &Color::rgba(v, v, v, 255))?;
         }
     }
 
@@ -318,7 +621,14 @@ fn sobel_both(
 /// Apply a gamma correction.
 ///
 /// Gamma can be a value from 0.01 - 9.99.
-/// A gamma < 1.0 will darken and a gamma > 1.0 will lighten the image.
+/// A gamma < 1.0 will darken and a gamma > 1.0 will lighten the image. A gamma of 1.0 leaves the
+/// image unchanged.
+///
+/// Each RGB channel is mapped using `255 * (value / 255) ^ (1 / gamma)`. Alpha is preserved.
+///
+/// # Errors
+///
+/// If gamma is outside the 0.01 - 9.99 range, this fails with `RasterError::InvalidGamma`.
 ///
 /// # Examples
 /// ```
@@ -337,29 +647,71 @@ fn sobel_both(
 ///
 // http://stackoverflow.com/questions/14088889/changing-a-color-brightness
 pub fn gamma(src: &mut Image, gamma: f32) -> RasterResult<()> {
-    let w: i32 = src.width;
-    let h: i32 = src.height;
-
     if gamma < 0.01 || gamma > 9.99 {
         return Err(RasterError::InvalidGamma(gamma));
     }
 
-    for y in 0..h {
-        for x in 0..w {
-            let p = src.get_pixel(x, y)?;
-            let r = (p.r as f32 / 255.0).powf(gamma) * 255.0;
-            let g = (p.g as f32 / 255.0).powf(gamma) * 255.0;
-            let b = (p.b as f32 / 255.0).powf(gamma) * 255.0;
+    // There are only 256 possible values per channel, compute them once.
+    let mut lut = [0u8; 256];
+    for (value, entry) in lut.iter_mut().enumerate() {
+        *entry = clamp_channel((value as f32 / 255.0).powf(1.0 / gamma) * 255.0);
+    }
 
-            src.set_pixel(x, y, &Color::rgba(r as u8, g as u8, b as u8, p.a as u8))?;
-        }
+    for pixel in src.bytes.chunks_mut(4) {
+        pixel[0] = lut[pixel[0] as usize];
+        pixel[1] = lut[pixel[1] as usize];
+        pixel[2] = lut[pixel[2] as usize];
+    }
+
+    Ok(())
+}
+
+/// Apply a Gaussian blur with the given standard deviation.
+///
+/// The kernel radius is `ceil(3 * sigma)` and the kernel is normalized, so the overall brightness
+/// is preserved. Samples outside the image are clamped to the nearest edge pixel. Alpha is blurred
+/// as well. A sigma of 0.0 or less leaves the image unchanged.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// filter::gaussian_blur(&mut image, 2.5).unwrap();
+/// raster::save(&image, "tests/out/test_filter_gaussian_blur_sigma.jpg").unwrap();
+/// ```
+///
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.jpg)
+///
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_gaussian_blur_sigma.jpg)
+///
+pub fn gaussian_blur(src: &mut Image, sigma: f32) -> RasterResult<()> {
+    if sigma <= 0.0 {
+        return Ok(());
+    }
+
+    let radius = (3.0 * sigma).ceil() as i64;
+    let mut kernel: Vec<f32> = (-radius..radius + 1)
+        .map(|i| (-((i * i) as f32) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f32 = kernel.iter().sum();
+    for k in &mut kernel {
+        *k /= total;
     }
 
+    convolve_pass(src, &kernel, true);
+    convolve_pass(src, &kernel, false);
     Ok(())
 }
 
 /// Turn into grayscale image.
 ///
+/// Each pixel is set to its luminance using the Rec. 601 weights, see `Color::luminance`. Alpha
+/// is preserved. Other weightings, like a plain average of the channels, could be offered as
+/// modes later.
+///
 /// # Examples
 /// ```
 /// use raster::filter;
@@ -382,90 +734,643 @@ pub fn grayscale(src: &mut Image) -> RasterResult<()> {
     for y in 0..h {
         for x in 0..w {
             let p = src.get_pixel(x, y)?;
-            let gray = (p.r as f32 * 0.3) + (p.g as f32 * 0.59) + (p.b as f32 * 0.11);
+            let gray = p.luminance();
 
-            src.set_pixel(
-                x,
-                y,
-                &Color::rgba(gray as u8, gray as u8, gray as u8, gray as u8),
-            )?;
+            src.set_pixel(x, y, &Color::rgba(gray, gray, gray, p.a))?;
         }
     }
 
     Ok(())
 }
 
-/// Change saturation.
+/// Rotate the hue of every pixel by the given degrees, wrapping around 360. Alpha is preserved.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// filter::hue_rotate(&mut image, 90.0).unwrap();
+/// raster::save(&image, "tests/out/test_filter_hue_rotate.jpg").unwrap();
+/// ```
+///
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.jpg)
 ///
-/// Pass a float value for sat. < 0.0 to decrease and > 0.0 to increase. Eg 0.5 for 50% increase
-/// in saturation.
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_hue_rotate.jpg)
 ///
-/// Note: Saturation does not look good at the moment.
+pub fn hue_rotate(src: &mut Image, degrees: f32) -> RasterResult<()> {
+    let w: i32 = src.width;
+    let h: i32 = src.height;
+
+    for y in 0..h {
+        for x in 0..w {
+            let p = src.get_pixel(x, y)?;
+            let (hue, sat, val) = p.hsv();
+            let c = Color::from_hsv((hue + degrees) % 360.0, sat, val);
+
+            src.set_pixel(x, y, &Color::rgba(c.r, c.g, c.b, p.a))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Invert the colors to create a negative image. Alpha is preserved.
 ///
 /// # Examples
 /// ```
 /// use raster::filter;
 ///
-/// // Create image from file
-/// let mut image = raster::open("tests/in/sample.png").unwrap();
-/// filter::saturation(&mut image, 0.5).unwrap();
-/// raster::save(&image, "tests/out/test_filter_saturation.jpg").unwrap();
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// filter::invert(&mut image).unwrap();
+/// raster::save(&image, "tests/out/test_filter_invert.jpg").unwrap();
 /// ```
 ///
 /// ### Before
-/// ![](https://kosinix.github.io/raster/in/sample.png)
+/// ![](https://kosinix.github.io/raster/in/sample.jpg)
 ///
 /// ### After
-/// ![](https://kosinix.github.io/raster/out/test_filter_saturation.jpg)
+/// ![](https://kosinix.github.io/raster/out/test_filter_invert.jpg)
 ///
-pub fn saturation(src: &mut Image, sat: f32) -> RasterResult<()> {
+pub fn invert(src: &mut Image) -> RasterResult<()> {
     let w: i32 = src.width;
     let h: i32 = src.height;
 
     for y in 0..h {
         for x in 0..w {
             let p = src.get_pixel(x, y)?;
-            let hsv = Color::to_hsv(p.r, p.g, p.b);
-            let s = hsv.1;
-            let factor = (100.0 - s) * sat; // use % remaining
-            let mut new_s = s + factor;
-            if new_s > 100.0 {
-                new_s = 100.0;
-            } else if new_s < 0.0 {
-                new_s = 0.0;
-            }
-            let rgb = Color::to_rgb(hsv.0, new_s, hsv.2);
 
-            src.set_pixel(x, y, &Color::rgb(rgb.0, rgb.1, rgb.2))?;
+            src.set_pixel(x, y, &Color::rgba(255 - p.r, 255 - p.g, 255 - p.b, p.a))?;
         }
     }
 
     Ok(())
 }
 
-/// Apply sharpen.
+/// Remap the tonal range of the RGB channels, like the Levels tool of image editors.
+///
+/// Values at or below `in_black` become `out_black` and values at or above `in_white` become
+/// `out_white`. Values in between are normalized, raised to `1 / gamma` and spread over the output
+/// range, so a gamma > 1.0 brightens the midtones and a gamma < 1.0 darkens them. Alpha is
+/// preserved. An input range of 0 - 255, a gamma of 1.0 and an output range of 0 - 255 leave the
+/// image unchanged.
+///
+/// # Errors
+///
+/// If gamma is outside the 0.01 - 9.99 range, this fails with `RasterError::InvalidGamma`.
 ///
 /// # Examples
 /// ```
 /// use raster::filter;
 ///
-/// // Create image from file
 /// let mut image = raster::open("tests/in/sample.jpg").unwrap();
-/// filter::sharpen(&mut image).unwrap();
-/// raster::save(&image, "tests/out/test_filter_sharpen.jpg").unwrap();
+/// filter::levels(&mut image, 30, 220, 1.2, 0, 255).unwrap();
+/// raster::save(&image, "tests/out/test_filter_levels.jpg").unwrap();
 /// ```
+///
 /// ### Before
 /// ![](https://kosinix.github.io/raster/in/sample.jpg)
 ///
 /// ### After
-/// ![](https://kosinix.github.io/raster/out/test_filter_sharpen.jpg)
+/// ![](https://kosinix.github.io/raster/out/test_filter_levels.jpg)
 ///
-pub fn sharpen(src: &mut Image) -> RasterResult<()> {
-    let matrix: [[i32; 3]; 3] = [[0, -1, 0], [-1, 5, -1], [0, -1, 0]];
-    convolve(src, matrix, 1)
-}
+pub fn levels(
+    src: &mut Image,
+    in_black: u8,
+    in_white: u8,
+    gamma: f32,
+    out_black: u8,
+    out_white: u8,
+) -> RasterResult<()> {
+    if !(0.01..=9.99).contains(&gamma) {
+        return Err(RasterError::InvalidGamma(gamma));
+    }
 
-// Private functions
+    let in_range = if in_white > in_black {
+        (in_white - in_black) as f32
+    } else {
+        1.0
+    };
+    let out_range = out_white as f32 - out_black as f32;
+
+    // There are only 256 possible values per channel, compute them once.
+    let mut lut = [0u8; 256];
+    for (value, entry) in lut.iter_mut().enumerate() {
+        let normalized = ((value as f32 - in_black as f32) / in_range).clamp(0.0, 1.0);
+        *entry = clamp_channel(out_black as f32 + normalized.powf(1.0 / gamma) * out_range);
+    }
+
+    for pixel in src.bytes.chunks_mut(4) {
+        pixel[0] = lut[pixel[0] as usize];
+        pixel[1] = lut[pixel[1] as usize];
+        pixel[2] = lut[pixel[2] as usize];
+    }
+
+    Ok(())
+}
+
+/// Simulate linear camera motion by averaging each pixel with its neighbors along a line.
+///
+/// The line is `length` pixels long, centered on the pixel, at `angle` degrees where 0 is
+/// horizontal and 90 is vertical. Samples past the edges use the nearest edge pixel. A length of 0
+/// or 1 leaves the image unchanged.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// filter::motion_blur(&mut image, 15, 30.0).unwrap();
+/// raster::save(&image, "tests/out/test_filter_motion_blur.jpg").unwrap();
+/// ```
+///
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.jpg)
+///
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_motion_blur.jpg)
+///
+pub fn motion_blur(src: &mut Image, length: u32, angle: f32) -> RasterResult<()> {
+    if length < 2 {
+        return Ok(());
+    }
+
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let half = (length - 1) as f32 / 2.0;
+    let offsets: Vec<(i64, i64)> = (0..length)
+        .map(|i| {
+            let t = i as f32 - half;
+            ((t * cos).round() as i64, (t * sin).round() as i64)
+        })
+        .collect();
+
+    let w = src.width as i64;
+    let h = src.height as i64;
+    let copy = src.bytes.clone();
+    for y in 0..h {
+        for x in 0..w {
+            let mut sum = [0u32; 4];
+            for &(dx, dy) in &offsets {
+                let sx = clamp_index(x + dx, w);
+                let sy = clamp_index(y + dy, h);
+                let start = ((sy * w + sx) * 4) as usize;
+                for (c, total) in sum.iter_mut().enumerate() {
+                    *total += copy[start + c] as u32;
+                }
+            }
+            let start = ((y * w + x) * 4) as usize;
+            for (c, total) in sum.iter().enumerate() {
+                src.bytes[start + c] = clamp_channel(*total as f32 / length as f32);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Change the opacity by multiplying the alpha of every pixel by a factor, eg. to fade an image
+/// before blending it as a watermark.
+///
+/// A factor of 0.0 makes the image fully transparent and 1.0 leaves it unchanged. The new alpha is
+/// clamped to 0 - 255. RGB is left untouched.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// let mut image = raster::open("tests/in/sample.png").unwrap();
+/// filter::opacity(&mut image, 0.5).unwrap();
+/// raster::save(&image, "tests/out/test_filter_opacity.png").unwrap();
+/// ```
+///
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.png)
+///
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_opacity.png)
+///
+pub fn opacity(src: &mut Image, factor: f32) -> RasterResult<()> {
+    for pixel in src.bytes.chunks_mut(4) {
+        pixel[3] = clamp_channel(pixel[3] as f32 * factor);
+    }
+
+    Ok(())
+}
+
+/// Reduce each RGB channel to the given number of levels, producing banded output.
+///
+/// Levels can be a value from 2 - 255. Alpha is preserved.
+///
+/// # Errors
+///
+/// If levels is below 2, this fails with `RasterError::InvalidPosterizeLevels`.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// filter::posterize(&mut image, 4).unwrap();
+/// raster::save(&image, "tests/out/test_filter_posterize.jpg").unwrap();
+/// ```
+///
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.jpg)
+///
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_posterize.jpg)
+///
+pub fn posterize(src: &mut Image, levels: u8) -> RasterResult<()> {
+    if levels < 2 {
+        return Err(RasterError::InvalidPosterizeLevels(levels));
+    }
+
+    let steps = (levels - 1) as f32;
+    let mut lut = [0u8; 256];
+    for (value, entry) in lut.iter_mut().enumerate() {
+        *entry = clamp_channel((value as f32 / 255.0 * steps).round() / steps * 255.0);
+    }
+
+    for pixel in src.bytes.chunks_mut(4) {
+        pixel[0] = lut[pixel[0] as usize];
+        pixel[1] = lut[pixel[1] as usize];
+        pixel[2] = lut[pixel[2] as usize];
+    }
+
+    Ok(())
+}
+
+/// Reduce the colors of an image to a palette of at most num_colors, eg. before saving as GIF.
+///
+/// The palette is built from the image using median cut and each pixel is replaced by the closest
+/// palette color. Alpha is preserved. See `dither` to reduce banding in gradients.
+///
+/// # Errors
+///
+/// A num_colors of 0 fails with `RasterError::InvalidPaletteSize`.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// filter::quantize(&mut image, 8).unwrap();
+/// raster::save(&image, "tests/out/test_filter_quantize.png").unwrap();
+/// ```
+///
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.jpg)
+///
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_quantize.png)
+///
+pub fn quantize(src: &mut Image, num_colors: usize) -> RasterResult<()> {
+    if num_colors == 0 {
+        return Err(RasterError::InvalidPaletteSize(num_colors));
+    }
+    let palette: Vec<Color> = palette::median_cut(src, num_colors)
+        .into_iter()
+        .map(|(color, _)| color)
+        .collect();
+
+    for pixel in src.bytes.chunks_mut(4) {
+        let i = palette::nearest(&palette, pixel[0] as f32, pixel[1] as f32, pixel[2] as f32);
+        pixel[0] = palette[i].r;
+        pixel[1] = palette[i].g;
+        pixel[2] = palette[i].b;
+    }
+
+    Ok(())
+}
+
+/// Change saturation.
+///
+/// Each RGB channel is moved toward or away from the pixel's luminance using
+/// `lum + (value - lum) * factor`. A factor of 0.0 gives a grayscale image, 1.0 leaves the image
+/// unchanged and values > 1.0 boost saturation. Alpha is preserved.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// // Create image from file
+/// let mut image = raster::open("tests/in/sample.png").unwrap();
+/// filter::saturation(&mut image, 1.5).unwrap();
+/// raster::save(&image, "tests/out/test_filter_saturation.jpg").unwrap();
+/// ```
+///
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.png)
+///
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_saturation.jpg)
+///
+pub fn saturation(src: &mut Image, factor: f32) -> RasterResult<()> {
+    let w: i32 = src.width;
+    let h: i32 = src.height;
+
+    for y in 0..h {
+        for x in 0..w {
+            let p = src.get_pixel(x, y)?;
+            let lum = p.luminance() as f32;
+            let r = lum + (p.r as f32 - lum) * factor;
+            let g = lum + (p.g as f32 - lum) * factor;
+            let b = lum + (p.b as f32 - lum) * factor;
+
+            src.set_pixel(
+                x,
+                y,
+                &Color::rgba(clamp_channel(r), clamp_channel(g), clamp_channel(b), p.a),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Apply a sepia tone.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// filter::sepia(&mut image).unwrap();
+/// raster::save(&image, "tests/out/test_filter_sepia.jpg").unwrap();
+/// ```
+///
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.jpg)
+///
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_sepia.jpg)
+///
+pub fn sepia(src: &mut Image) -> RasterResult<()> {
+    let w: i32 = src.width;
+    let h: i32 = src.height;
+
+    for y in 0..h {
+        for x in 0..w {
+            let p = src.get_pixel(x, y)?;
+            let (r, g, b) = (p.r as f32, p.g as f32, p.b as f32);
+            let r2 = (0.393 * r) + (0.769 * g) + (0.189 * b);
+            let g2 = (0.349 * r) + (0.686 * g) + (0.168 * b);
+            let b2 = (0.272 * r) + (0.534 * g) + (0.131 * b);
+
+            src.set_pixel(
+                x,
+                y,
+                &Color::rgba(clamp_channel(r2), clamp_channel(g2), clamp_channel(b2), p.a),
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Apply sharpen.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// // Create image from file
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// filter::sharpen(&mut image).unwrap();
+/// raster::save(&image, "tests/out/test_filter_sharpen.jpg").unwrap();
+/// ```
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.jpg)
+///
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_sharpen.jpg)
+///
+pub fn sharpen(src: &mut Image) -> RasterResult<()> {
+    let matrix: [[i32; 3]; 3] = [[0, -1, 0], [-1, 5, -1], [0, -1, 0]];
+    convolve(src, matrix, 1)
+}
+
+/// Turn into a black and white image.
+///
+/// Pixels with a luminance at or above level become white, the rest become black. This is a
+/// global threshold, the same level is used for the whole image regardless of local contrast.
+/// Alpha is preserved.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// filter::threshold(&mut image, 128).unwrap();
+/// raster::save(&image, "tests/out/test_filter_threshold.jpg").unwrap();
+/// ```
+///
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.jpg)
+///
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_threshold.jpg)
+///
+pub fn threshold(src: &mut Image, level: u8) -> RasterResult<()> {
+    let w: i32 = src.width;
+    let h: i32 = src.height;
+
+    for y in 0..h {
+        for x in 0..w {
+            let p = src.get_pixel(x, y)?;
+            let v = if p.luminance() >= level { 255 } else { 0 };
+
+            src.set_pixel(x, y, &Color::rgba(v, v, v, p.a))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Sharpen using an unsharp mask.
+///
+/// A copy of the image is blurred with `gaussian_blur` and each RGB channel becomes
+/// `original + amount * (original - blurred)`, clamped to 0 - 255. An amount of 0.0 leaves the
+/// image unchanged. Alpha is preserved. For a fixed 3x3 sharpen, see `sharpen`.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// filter::unsharp_mask(&mut image, 1.5).unwrap();
+/// raster::save(&image, "tests/out/test_filter_unsharp_mask.jpg").unwrap();
+/// ```
+///
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.jpg)
+///
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_unsharp_mask.jpg)
+///
+pub fn unsharp_mask(src: &mut Image, amount: f32) -> RasterResult<()> {
+    let mut blurred = src.clone();
+    gaussian_blur(&mut blurred, 1.0)?;
+
+    for (pixel, low) in src.bytes.chunks_mut(4).zip(blurred.bytes.chunks(4)) {
+        for c in 0..3 {
+            let v = pixel[c] as f32;
+            pixel[c] = clamp_channel(v + amount * (v - low[c] as f32));
+        }
+    }
+
+    Ok(())
+}
+
+/// Darken an image toward its corners.
+///
+/// Each pixel is darkened by `strength * d²`, where d is its distance from the center divided by
+/// the distance from the center to a corner. The center is unchanged and with a strength of 1.0
+/// the corners become black. Alpha is preserved.
+///
+/// # Examples
+/// ```
+/// use raster::filter;
+///
+/// let mut image = raster::open("tests/in/sample.jpg").unwrap();
+/// filter::vignette(&mut image, 0.8).unwrap();
+/// raster::save(&image, "tests/out/test_filter_vignette.jpg").unwrap();
+/// ```
+///
+/// ### Before
+/// ![](https://kosinix.github.io/raster/in/sample.jpg)
+///
+/// ### After
+/// ![](https://kosinix.github.io/raster/out/test_filter_vignette.jpg)
+///
+pub fn vignette(src: &mut Image, strength: f32) -> RasterResult<()> {
+    let center_x = (src.width - 1) as f32 / 2.0;
+    let center_y = (src.height - 1) as f32 / 2.0;
+    let corner = center_x * center_x + center_y * center_y;
+    if corner == 0.0 {
+        return Ok(());
+    }
+
+    let w = src.width as usize;
+    for (i, pixel) in src.bytes.chunks_mut(4).enumerate() {
+        let dx = (i % w) as f32 - center_x;
+        let dy = (i / w) as f32 - center_y;
+        let factor = (1.0 - strength * (dx * dx + dy * dy) / corner).clamp(0.0, 1.0);
+        for c in pixel.iter_mut().take(3) {
+            *c = clamp_channel(*c as f32 * factor);
+        }
+    }
+
+    Ok(())
+}
+
+// Private functions
+
+// Round and clamp a channel value to 0 - 255.
+fn clamp_channel(value: f32) -> u8 {
+    if value < 0.0 {
+        0
+    } else if value > 255.0 {
+        255
+    } else {
+        value.round() as u8
+    }
+}
+
+// Advance the state and return the next pseudo-random number of the SplitMix64 generator.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Clamp a sample coordinate to 0 - (len - 1).
+fn clamp_index(i: i64, len: i64) -> i64 {
+    if i < 0 {
+        0
+    } else if i >= len {
+        len - 1
+    } else {
+        i
+    }
+}
+
+// Box blur a single direction using a running sum per channel.
+fn box_blur_pass(src: &mut Image, radius: i64, horizontal: bool) {
+    let (lines, len, step, stride) = line_layout(src, horizontal);
+    let window = 2 * radius + 1;
+    let mut line = vec![0u8; len * 4];
+
+    for l in 0..lines {
+        let base = l * stride;
+        let index = |i: i64| base + clamp_index(i, len as i64) as usize * step;
+
+        let mut sum = [0i64; 4];
+        for i in -radius..radius + 1 {
+            let start = index(i);
+            for (c, s) in sum.iter_mut().enumerate() {
+                *s += src.bytes[start + c] as i64;
+            }
+        }
+
+        for i in 0..len as i64 {
+            let (add, remove) = (index(i + radius + 1), index(i - radius));
+            for (c, s) in sum.iter_mut().enumerate() {
+                line[i as usize * 4 + c] = ((*s + window / 2) / window) as u8;
+                *s += src.bytes[add + c] as i64 - src.bytes[remove + c] as i64;
+            }
+        }
+
+        for i in 0..len {
+            let start = base + i * step;
+            src.bytes[start..start + 4].copy_from_slice(&line[i * 4..i * 4 + 4]);
+        }
+    }
+}
+
+// Apply a 1D kernel of odd length in a single direction.
+fn convolve_pass(src: &mut Image, kernel: &[f32], horizontal: bool) {
+    let (lines, len, step, stride) = line_layout(src, horizontal);
+    let radius = (kernel.len() / 2) as i64;
+    let mut line = vec![0u8; len * 4];
+
+    for l in 0..lines {
+        let base = l * stride;
+
+        for i in 0..len as i64 {
+            let mut sum = [0.0f32; 4];
+            for (k, weight) in (i - radius..).zip(kernel.iter()) {
+                let start = base + clamp_index(k, len as i64) as usize * step;
+                for (c, s) in sum.iter_mut().enumerate() {
+                    *s += src.bytes[start + c] as f32 * weight;
+                }
+            }
+            for (c, s) in sum.iter().enumerate() {
+                line[i as usize * 4 + c] = clamp_channel(*s);
+            }
+        }
+
+        for i in 0..len {
+            let start = base + i * step;
+            src.bytes[start..start + 4].copy_from_slice(&line[i * 4..i * 4 + 4]);
+        }
+    }
+}
+
+// Returns the number of lines, the pixels per line, the byte step between pixels of a line and
+// the byte stride between lines. Lines are rows when horizontal, columns otherwise.
+fn line_layout(src: &Image, horizontal: bool) -> (usize, usize, usize, usize) {
+    let (w, h) = (src.width as usize, src.height as usize);
+    if horizontal {
+        (h, w, 4, w * 4)
+    } else {
+        (w, h, w * 4, 4)
+    }
+}
 
 // Box
 fn blur_box(src: &mut Image) -> RasterResult<()> {
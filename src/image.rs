@@ -1,13 +1,17 @@
 //!  A module for generic representation of image.
 
 // from rust
-use std::collections::HashMap;
+use std::cmp;
+use std::str::FromStr;
 
 // from external crate
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 // from local crate
 use error::{RasterError, RasterResult};
 use color::Color;
+use palette;
 
 /// A struct for easily representing a raster image.
 #[derive(Debug, Clone)]
@@ -18,7 +22,7 @@ pub struct Image {
     /// Height of image in pixels.
     pub height: i32,
 
-    /// Vector containing sequence of bytes in RGBA format.
+    /// Vector containing sequence of bytes in RGBA format, row by row from the top left.
     pub bytes: Vec<u8>,
 }
 
@@ -38,10 +42,26 @@ impl<'a> Image {
     /// assert_eq!(image.height, 2);
     /// ```
     pub fn blank(w: i32, h: i32) -> Image {
+        Image::blank_with_color(w, h, Color::black())
+    }
+
+    /// Create a blank image filled with a color, in a single pass.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::{Color, Image};
+    ///
+    /// let image = Image::blank_with_color(2, 2, Color::rgba(255, 0, 0, 128));
+    ///
+    /// assert_eq!(255, image.get_pixel(1, 1).unwrap().r);
+    /// assert_eq!(128, image.get_pixel(1, 1).unwrap().a);
+    /// ```
+    pub fn blank_with_color(w: i32, h: i32, color: Color) -> Image {
         let mut bytes = Vec::with_capacity((w * h) as usize * 4);
         for _ in 0..h {
             for _ in 0..w {
-                bytes.extend_from_slice(&[0, 0, 0, 255]);
+                bytes.extend_from_slice(&[color.r, color.g, color.b, color.a]);
             }
         }
         Image {
@@ -51,6 +71,96 @@ impl<'a> Image {
         }
     }
 
+    /// Get the most common colors of an image, most popular first.
+    ///
+    /// The colors are found using median cut. Large images are sampled on a grid first for speed.
+    /// Fewer colors are returned if the image doesn't have enough distinct colors. Alpha is
+    /// ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Image;
+    ///
+    /// let image = raster::open("tests/in/sample.jpg").unwrap();
+    ///
+    /// for color in image.dominant_colors(5) {
+    ///     println!("{}", color.to_hex());
+    /// }
+    /// ```
+    pub fn dominant_colors(&self, count: usize) -> Vec<Color> {
+        palette::median_cut(self, count)
+            .into_iter()
+            .map(|(color, _)| color)
+            .collect()
+    }
+
+    /// Get a single channel as a grayscale image, with the channel value copied into RGB and an
+    /// opaque alpha.
+    ///
+    /// The channel is one of `"red"`, `"green"`, `"blue"` or `"alpha"`.
+    ///
+    /// # Errors
+    ///
+    /// An unknown channel name fails with `RasterError::InvalidChannel`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::{editor, Color, Image};
+    ///
+    /// let mut image = Image::blank(2, 2);
+    /// editor::fill(&mut image, Color::rgba(200, 100, 50, 255)).unwrap();
+    /// let green = image.extract_channel("green").unwrap();
+    ///
+    /// assert_eq!(100, green.get_pixel(0, 0).unwrap().r);
+    /// assert_eq!(100, green.get_pixel(0, 0).unwrap().b);
+    /// ```
+    pub fn extract_channel(&self, channel: &str) -> RasterResult<Image> {
+        match channel {
+            "red" => Ok(channel_image(self, 0)),
+            "green" => Ok(channel_image(self, 1)),
+            "blue" => Ok(channel_image(self, 2)),
+            "alpha" => Ok(channel_image(self, 3)),
+            _ => Err(RasterError::InvalidChannel(channel.to_string())),
+        }
+    }
+
+    /// Create an image from raw RGBA bytes, eg. from another library, without decoding.
+    ///
+    /// The bytes are laid out row by row from the top left, 4 bytes per pixel.
+    ///
+    /// # Errors
+    ///
+    /// If the number of bytes is not width * height * 4, or either dimension is negative, this
+    /// fails with `RasterError::BufferSizeMismatch`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Image;
+    ///
+    /// let image = Image::from_raw(1, 2, vec![255, 0, 0, 255, 0, 0, 255, 255]).unwrap();
+    ///
+    /// assert_eq!(255, image.get_pixel(0, 1).unwrap().b);
+    /// assert!(Image::from_raw(2, 2, vec![0; 8]).is_err());
+    /// ```
+    pub fn from_raw(width: i32, height: i32, bytes: Vec<u8>) -> RasterResult<Image> {
+        let expected = cmp::max(0, width) as usize * cmp::max(0, height) as usize * 4;
+        if width < 0 || height < 0 || bytes.len() != expected {
+            return Err(RasterError::BufferSizeMismatch {
+                expected,
+                found: bytes.len(),
+            });
+        }
+
+        Ok(Image {
+            width,
+            height,
+            bytes,
+        })
+    }
+
     /// Check if there is a pixel at this location given by x and y.
     ///
     /// # Examples
@@ -72,6 +182,47 @@ impl<'a> Image {
         }
     }
 
+    /// Measure how different another image of the same size is, as the mean squared error of
+    /// all RGBA channels normalized to 0.0 - 1.0.
+    ///
+    /// Identical images give 0.0 and an image compared to its inverse with the same alpha gives
+    /// 0.75. Unlike `compare::similar`, which finds similar pictures, this measures exact pixel
+    /// differences and suits regression tests.
+    ///
+    /// # Errors
+    ///
+    /// If the images differ in size, this fails with `RasterError::DimensionMismatch`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::{Color, Image};
+    ///
+    /// let image1 = Image::blank(2, 2);
+    /// let mut image2 = Image::blank(2, 2);
+    /// image2.set_pixel(0, 0, &Color::rgba(255, 255, 255, 255)).unwrap();
+    ///
+    /// assert_eq!(0.0, image1.compare(&image1).unwrap());
+    /// assert!(image1.compare(&image2).unwrap() > 0.0);
+    /// ```
+    pub fn compare(&self, other: &Image) -> RasterResult<f64> {
+        check_dimensions(self, other)?;
+
+        if self.bytes.is_empty() {
+            return Ok(0.0);
+        }
+        let sum: f64 = self
+            .bytes
+            .iter()
+            .zip(&other.bytes)
+            .map(|(&a, &b)| {
+                let d = a as f64 - b as f64;
+                d * d
+            })
+            .sum();
+        Ok(sum / (self.bytes.len() as f64 * 255.0 * 255.0))
+    }
+
     /// Get the histogram of the image.
     ///
     /// # Examples
@@ -90,14 +241,9 @@ impl<'a> Image {
     ///
     /// let image = raster::open("tests/in/sample.png").unwrap();
     ///
-    /// let (r_bin, _, _, _) = image.histogram().unwrap();
+    /// let histogram = image.histogram();
     ///
-    /// let mut max_r_bin = 0;
-    /// for (_, count) in &r_bin {
-    ///     if *count > max_r_bin {
-    ///         max_r_bin = *count;
-    ///     }
-    /// }
+    /// let max_r_bin = *histogram.r.iter().max().unwrap();
     ///
     /// let canvas_w = 256;
     /// let canvas_h: i32 = 100;
@@ -105,16 +251,11 @@ impl<'a> Image {
     /// raster::editor::fill(&mut image, Color::rgb(214, 214, 214)).unwrap();
     ///
     /// for x in 0..256 as i32 { // 0-255
-    ///     let key = x as u8;
-    ///     match r_bin.get(&key) {
-    ///         Some(count) => {
-    ///             let height = (canvas_h as f32 * (*count as f32 / max_r_bin as f32)).round() as i32;
-    ///
-    ///             for y in canvas_h-height..canvas_h {
-    ///                 image.set_pixel(x, y, &Color::hex("#e22d11").unwrap()).unwrap();
-    ///             }
-    ///         },
-    ///         None => {}
+    ///     let count = histogram.r[x as usize];
+    ///     let height = (canvas_h as f32 * (count as f32 / max_r_bin as f32)).round() as i32;
+    ///
+    ///     for y in canvas_h-height..canvas_h {
+    ///         image.set_pixel(x, y, &Color::hex("#e22d11").unwrap()).unwrap();
     ///     }
     /// }
     ///
@@ -129,35 +270,162 @@ impl<'a> Image {
     ///
     /// ![](https://kosinix.github.io/raster/in/histogram-ps.png)
     ///
-    pub fn histogram(&self) -> RasterResult<Histogram> {
-        let w = self.width;
-        let h = self.height;
+    pub fn histogram(&self) -> Histogram {
+        let mut histogram = Histogram {
+            r: [0; 256],
+            g: [0; 256],
+            b: [0; 256],
+            luminance: [0; 256],
+        };
+        for p in self.bytes.chunks(4) {
+            histogram.r[p[0] as usize] += 1;
+            histogram.g[p[1] as usize] += 1;
+            histogram.b[p[2] as usize] += 1;
+            histogram.luminance[Color::rgb(p[0], p[1], p[2]).luminance() as usize] += 1;
+        }
 
-        let mut r_bin: HashMap<u8, u32> = HashMap::new();
-        let mut g_bin: HashMap<u8, u32> = HashMap::new();
-        let mut b_bin: HashMap<u8, u32> = HashMap::new();
-        let mut a_bin: HashMap<u8, u32> = HashMap::new();
-        for y in 0..h {
-            for x in 0..w {
-                let pixel = self.get_pixel(x, y)?;
+        histogram
+    }
+
+    /// Check if another image has the same dimensions and exactly the same RGBA bytes.
+    ///
+    /// Unlike `compare::equal`, alpha is compared too and differently sized images are simply not
+    /// equal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Image;
+    ///
+    /// let image = Image::blank(2, 2);
+    ///
+    /// assert!(image.is_equal(&image.clone()));
+    /// assert!(!image.is_equal(&Image::blank(2, 3)));
+    /// ```
+    pub fn is_equal(&self, other: &Image) -> bool {
+        self.width == other.width && self.height == other.height && self.bytes == other.bytes
+    }
 
-                // Insert the key with a value of 0 if key does not exist yet. Then return the
-                // count (which is zero).
-                let r_bin_c = r_bin.entry(pixel.r).or_insert(0);
-                *r_bin_c += 1; // +1 to the count.
+    /// Combine four grayscale images into one RGBA image. The inverse of `split_channels`.
+    ///
+    /// The red channel of each image is used as the intensity of the matching channel.
+    ///
+    /// # Errors
+    ///
+    /// If the images differ in size, this fails with `RasterError::DimensionMismatch`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Image;
+    ///
+    /// let image = raster::open("tests/in/sample.png").unwrap();
+    /// let [r, g, b, a] = image.split_channels();
+    /// let merged = Image::merge_channels(&r, &g, &b, &a).unwrap();
+    ///
+    /// assert!(merged.is_equal(&image));
+    /// ```
+    pub fn merge_channels(r: &Image, g: &Image, b: &Image, a: &Image) -> RasterResult<Image> {
+        check_dimensions(r, g)?;
+        check_dimensions(r, b)?;
+        check_dimensions(r, a)?;
 
-                let g_bin_c = g_bin.entry(pixel.g).or_insert(0);
-                *g_bin_c += 1;
+        let mut dest = Image::blank(r.width, r.height);
+        for (i, pixel) in dest.bytes.chunks_mut(4).enumerate() {
+            let start = i * 4;
+            pixel[0] = r.bytes[start];
+            pixel[1] = g.bytes[start];
+            pixel[2] = b.bytes[start];
+            pixel[3] = a.bytes[start];
+        }
 
-                let b_bin_c = b_bin.entry(pixel.b).or_insert(0);
-                *b_bin_c += 1;
+        Ok(dest)
+    }
 
-                let a_bin_c = a_bin.entry(pixel.a).or_insert(0);
-                *a_bin_c += 1;
-            }
+    /// Apply a function to every pixel in place. The function gets the x and y coordinates and
+    /// the current color, and returns the new color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::{Color, Image};
+    ///
+    /// let mut image = Image::blank(2, 2);
+    ///
+    /// // Invert
+    /// image.map_pixels(|_, _, p| Color::rgba(255 - p.r, 255 - p.g, 255 - p.b, p.a));
+    ///
+    /// assert_eq!(255, image.get_pixel(1, 1).unwrap().r);
+    /// ```
+    pub fn map_pixels<F>(&mut self, mut f: F)
+    where
+        F: FnMut(i32, i32, Color) -> Color,
+    {
+        let w = self.width;
+        for (i, p) in self.bytes.chunks_mut(4).enumerate() {
+            let color = Color::rgba(p[0], p[1], p[2], p[3]);
+            let color = f(i as i32 % w, i as i32 / w, color);
+            p.copy_from_slice(&[color.r, color.g, color.b, color.a]);
         }
+    }
 
-        Ok((r_bin, g_bin, b_bin, a_bin))
+    /// Iterate over the x and y coordinates and color of every pixel, row by row from the top
+    /// left.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Image;
+    ///
+    /// let image = Image::blank(3, 2);
+    ///
+    /// let (x, y, color) = image.pixels().last().unwrap();
+    /// assert_eq!((2, 1, 255), (x, y, color.a));
+    /// ```
+    pub fn pixels(&self) -> impl Iterator<Item = (i32, i32, Color)> + '_ {
+        let w = self.width;
+        self.bytes
+            .chunks(4)
+            .enumerate()
+            .map(move |(i, p)| (i as i32 % w, i as i32 / w, Color::rgba(p[0], p[1], p[2], p[3])))
+    }
+
+    /// Multiply the RGB of every pixel by its alpha, see `Color::premultiply`.
+    ///
+    /// Use this before handing the bytes to a renderer that expects premultiplied alpha. The rest
+    /// of raster works with straight alpha, so call `unpremultiply_alpha` before editing further.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::{editor, Color, Image};
+    ///
+    /// let mut image = Image::blank(2, 2);
+    /// editor::fill(&mut image, Color::rgba(255, 0, 0, 128)).unwrap();
+    /// image.premultiply_alpha();
+    ///
+    /// assert_eq!(128, image.get_pixel(0, 0).unwrap().r);
+    /// ```
+    pub fn premultiply_alpha(&mut self) {
+        self.map_pixels(|_, _, p| p.premultiply());
+    }
+
+    /// Divide the RGB of every pixel by its alpha, see `Color::unpremultiply`. The inverse of
+    /// `premultiply_alpha`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::{editor, Color, Image};
+    ///
+    /// let mut image = Image::blank(2, 2);
+    /// editor::fill(&mut image, Color::rgba(128, 0, 0, 128)).unwrap();
+    /// image.unpremultiply_alpha();
+    ///
+    /// assert_eq!(255, image.get_pixel(0, 0).unwrap().r);
+    /// ```
+    pub fn unpremultiply_alpha(&mut self) {
+        self.map_pixels(|_, _, p| p.unpremultiply());
     }
 
     /// Get pixel in a given x and y location of an image.
@@ -202,6 +470,48 @@ impl<'a> Image {
         }
     }
 
+    /// Copy a rectangular region of the image into a new image, eg. a sprite from a sprite sheet.
+    ///
+    /// # Errors
+    ///
+    /// If the region extends past the image, this fails with `RasterError::PixelOutOfBounds` for
+    /// the corner of the region that is outside.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Image;
+    ///
+    /// let image = Image::blank(4, 4);
+    ///
+    /// let region = image.get_region(2, 2, 2, 2).unwrap();
+    /// assert_eq!(region.width, 2);
+    /// assert_eq!(region.height, 2);
+    ///
+    /// assert!(image.get_region(3, 3, 2, 2).is_err());
+    /// ```
+    pub fn get_region(&self, x: i32, y: i32, w: i32, h: i32) -> RasterResult<Image> {
+        if x < 0 || y < 0 || w < 0 || h < 0 {
+            return Err(RasterError::PixelOutOfBounds(x, y));
+        }
+        if x + w > self.width || y + h > self.height {
+            return Err(RasterError::PixelOutOfBounds(x + w - 1, y + h - 1));
+        }
+
+        let row_len = w as usize * 4;
+        let mut bytes = Vec::with_capacity(row_len * h as usize);
+        for row in y..y + h {
+            let start = (row * self.width + x) as usize * 4;
+            bytes.extend_from_slice(&self.bytes[start..start + row_len]);
+        }
+
+        Ok(Image {
+            width: w,
+            height: h,
+            bytes,
+        })
+    }
+
     /// Set pixel in a given x and y location of an image.
     ///
     /// # Errors
@@ -247,20 +557,238 @@ impl<'a> Image {
             Ok(())
         }
     }
+
+    /// Paste an image with its top left corner at x and y, replacing the pixels underneath.
+    ///
+    /// Parts of the pasted image that fall outside this image are clipped. Pixels are copied as
+    /// is, use `editor::blend` to blend transparent pixels instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::{editor, Color, Image};
+    ///
+    /// let mut canvas = Image::blank(4, 4);
+    /// let mut red = Image::blank(2, 2);
+    /// editor::fill(&mut red, Color::red()).unwrap();
+    ///
+    /// canvas.set_region(3, 3, &red).unwrap();
+    ///
+    /// assert_eq!(255, canvas.get_pixel(3, 3).unwrap().r);
+    /// assert_eq!(0, canvas.get_pixel(2, 2).unwrap().r);
+    /// ```
+    pub fn set_region(&mut self, x: i32, y: i32, src: &Image) -> RasterResult<()> {
+        // Part of src that lands inside this image
+        let src_x = cmp::max(0, -x);
+        let src_y = cmp::max(0, -y);
+        let w = cmp::min(src.width, self.width - x) - src_x;
+        let h = cmp::min(src.height, self.height - y) - src_y;
+        if w <= 0 || h <= 0 {
+            return Ok(());
+        }
+
+        let row_len = w as usize * 4;
+        for row in 0..h {
+            let from = ((src_y + row) * src.width + src_x) as usize * 4;
+            let to = ((y + src_y + row) * self.width + x + src_x) as usize * 4;
+            self.bytes[to..to + row_len].copy_from_slice(&src.bytes[from..from + row_len]);
+        }
+
+        Ok(())
+    }
+
+    /// Split into red, green, blue and alpha grayscale images, in that order. See
+    /// `extract_channel`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::{editor, Color, Image};
+    ///
+    /// let mut image = Image::blank(2, 2);
+    /// editor::fill(&mut image, Color::rgba(200, 100, 50, 25)).unwrap();
+    /// let [_, _, blue, alpha] = image.split_channels();
+    ///
+    /// assert_eq!(50, blue.get_pixel(0, 0).unwrap().r);
+    /// assert_eq!(25, alpha.get_pixel(0, 0).unwrap().r);
+    /// ```
+    pub fn split_channels(&self) -> [Image; 4] {
+        [
+            channel_image(self, 0),
+            channel_image(self, 1),
+            channel_image(self, 2),
+            channel_image(self, 3),
+        ]
+    }
+
+    /// Get the raw pixels, eg. to hand them to a GPU uploader or another crate without copying.
+    ///
+    /// The bytes are laid out row by row from the top left, 4 bytes per pixel in RGBA order with
+    /// 8 bits per channel. The length is always width * height * 4.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Image;
+    ///
+    /// let image = Image::blank(2, 2);
+    ///
+    /// assert_eq!(16, image.to_bytes().len());
+    /// assert_eq!(&[0, 0, 0, 255], &image.to_bytes()[0..4]);
+    /// ```
+    pub fn to_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Consume the image, returning its raw pixels. The layout is the same as `to_bytes`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Image;
+    ///
+    /// let bytes = Image::blank(2, 2).into_bytes();
+    ///
+    /// assert_eq!(16, bytes.len());
+    /// ```
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
 }
 
-/// Holds histogram information.
-pub type Histogram = (
-    HashMap<u8, u32>,
-    HashMap<u8, u32>,
-    HashMap<u8, u32>,
-    HashMap<u8, u32>,
-);
+/// Holds histogram information. Each array counts the pixels for every value from 0 to 255.
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    /// Red channel.
+    pub r: [u32; 256],
+    /// Green channel.
+    pub g: [u32; 256],
+    /// Blue channel.
+    pub b: [u32; 256],
+    /// Perceived brightness, see `Color::luminance`.
+    pub luminance: [u32; 256],
+}
+
+impl Histogram {
+    /// Get the most common value of the red, green, blue and luminance channels. Ties go to the
+    /// lowest value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use raster::Image;
+    ///
+    /// let image = Image::blank(2, 2);
+    ///
+    /// assert_eq!((0, 0, 0, 0), image.histogram().peaks());
+    /// ```
+    pub fn peaks(&self) -> (u8, u8, u8, u8) {
+        (
+            peak(&self.r),
+            peak(&self.g),
+            peak(&self.b),
+            peak(&self.luminance),
+        )
+    }
+}
 
 /// Enumeration of supported raster formats.
-#[derive(Debug)]
+///
+/// Formats can also be parsed from their file extensions, eg. "png" or "jpg".
+///
+/// # Examples
+/// ```
+/// use raster::ImageFormat;
+///
+/// let format: ImageFormat = "jpeg".parse().unwrap();
+/// assert_eq!(ImageFormat::Jpeg, format);
+/// ```
+#[derive(Debug, PartialEq)]
 pub enum ImageFormat {
+    Bmp,
     Gif,
     Jpeg,
     Png,
+    Webp,
+}
+
+/// Parse an image format from a file extension. Case is ignored.
+///
+/// # Errors
+///
+/// Unknown extensions fail with `RasterError::UnsupportedFormat`.
+impl FromStr for ImageFormat {
+    type Err = RasterError;
+
+    fn from_str(s: &str) -> RasterResult<ImageFormat> {
+        match &s.to_lowercase()[..] {
+            "bmp" => Ok(ImageFormat::Bmp),
+            "gif" => Ok(ImageFormat::Gif),
+            "jpg" | "jpeg" => Ok(ImageFormat::Jpeg),
+            "png" => Ok(ImageFormat::Png),
+            "webp" => Ok(ImageFormat::Webp),
+            _ => Err(RasterError::UnsupportedFormat(s.to_string())),
+        }
+    }
+}
+
+// Compute every row of an image with f, which gets the y coordinate and the bytes of the row.
+// With the rayon feature, rows are computed in parallel.
+pub fn fill_rows<F>(image: &mut Image, f: F)
+where
+    F: Fn(i32, &mut [u8]) + Send + Sync,
+{
+    let row_len = image.width as usize * 4;
+    if row_len == 0 {
+        return;
+    }
+
+    #[cfg(feature = "rayon")]
+    image
+        .bytes
+        .par_chunks_mut(row_len)
+        .enumerate()
+        .for_each(|(y, row)| f(y as i32, row));
+
+    #[cfg(not(feature = "rayon"))]
+    image
+        .bytes
+        .chunks_mut(row_len)
+        .enumerate()
+        .for_each(|(y, row)| f(y as i32, row));
+}
+
+// Fail with RasterError::DimensionMismatch unless found is the same size as expected.
+pub fn check_dimensions(expected: &Image, found: &Image) -> RasterResult<()> {
+    if (expected.width, expected.height) != (found.width, found.height) {
+        return Err(RasterError::DimensionMismatch {
+            expected: (expected.width, expected.height),
+            found: (found.width, found.height),
+        });
+    }
+    Ok(())
+}
+
+// Private functions
+
+// A grayscale copy of one channel, 0 - 3 for RGBA, with an opaque alpha.
+fn channel_image(image: &Image, index: usize) -> Image {
+    let mut dest = Image::blank(image.width, image.height);
+    for (pixel, p) in dest.bytes.chunks_mut(4).zip(image.bytes.chunks(4)) {
+        pixel[0] = p[index];
+        pixel[1] = p[index];
+        pixel[2] = p[index];
+    }
+    dest
+}
+
+// The value with the highest count in a histogram channel.
+fn peak(bins: &[u32; 256]) -> u8 {
+    let mut peak = 0;
+    for (value, &count) in bins.iter().enumerate() {
+        if count > bins[peak] {
+            peak = value;
+        }
+    }
+    peak as u8
 }
@@ -2,19 +2,23 @@
 
 // from rust
 use std::cmp;
+use std::f32;
 
 // from external crate
 
 // from local crate
 use error::RasterResult;
+use image;
 use Image;
-use Color;
 
 /// An enum for the various modes that can be used for interpolation.
 #[derive(Debug)]
 pub enum InterpolationMode {
     Bilinear,
     Bicubic,
+    /// Windowed sinc over 3 pixels on each side, widened when downscaling. Slower, but keeps
+    /// detail and avoids aliasing when making images smaller.
+    Lanczos3,
     Nearest,
 }
 
@@ -28,25 +32,73 @@ pub fn resample(
     match interpolation {
         InterpolationMode::Bilinear => bilinear(src, w, h),
         InterpolationMode::Bicubic => bilinear(src, w, h), // TODO: bicubic
+        InterpolationMode::Lanczos3 => lanczos3(src, w, h),
         InterpolationMode::Nearest => nearest(src, w, h),
     }
 }
 
+/// Interpolate using a Lanczos kernel with a radius of 3.
+///
+/// When downscaling, the kernel is stretched to cover every source pixel that lands in an output
+/// pixel, and the weights are normalized so flat areas keep their color.
+pub fn lanczos3(src: &mut Image, w: i32, h: i32) -> RasterResult<()> {
+    // Horizontal pass into a w x height image, then a vertical pass into w x h
+    let src_w = src.width;
+    let columns = lanczos_weights(src.width, w);
+    let mut horizontal = Image::blank(w, src.height);
+    image::fill_rows(&mut horizontal, |y, row| {
+        for (pixel, weights) in row.chunks_mut(4).zip(&columns) {
+            let mut sum = [0.0f32; 4];
+            for &(x, weight) in weights {
+                let start = ((y * src_w + x) * 4) as usize;
+                for (c, total) in sum.iter_mut().enumerate() {
+                    *total += src.bytes[start + c] as f32 * weight;
+                }
+            }
+            for (value, total) in pixel.iter_mut().zip(&sum) {
+                *value = clamp_channel(*total);
+            }
+        }
+    });
+
+    let rows = lanczos_weights(src.height, h);
+    let mut dest = Image::blank(w, h);
+    image::fill_rows(&mut dest, |y, row| {
+        for (x, pixel) in row.chunks_mut(4).enumerate() {
+            let mut sum = [0.0f32; 4];
+            for &(src_y, weight) in &rows[y as usize] {
+                let start = ((src_y * w) as usize + x) * 4;
+                for (c, total) in sum.iter_mut().enumerate() {
+                    *total += horizontal.bytes[start + c] as f32 * weight;
+                }
+            }
+            for (value, total) in pixel.iter_mut().zip(&sum) {
+                *value = clamp_channel(*total);
+            }
+        }
+    });
+    src.width = dest.width;
+    src.height = dest.height;
+    src.bytes = dest.bytes;
+
+    Ok(())
+}
+
 /// Interpolate using nearest neighbor.
 pub fn nearest(src: &mut Image, w: i32, h: i32) -> RasterResult<()> {
     let x_ratio: f64 = src.width as f64 / w as f64;
     let y_ratio: f64 = src.height as f64 / h as f64;
 
+    let src_w = src.width;
     let mut dest = Image::blank(w, h);
-    for y in 0..h {
-        for x in 0..w {
+    image::fill_rows(&mut dest, |y, row| {
+        let py: i32 = (y as f64 * y_ratio).floor() as i32;
+        for (x, pixel) in row.chunks_mut(4).enumerate() {
             let px: i32 = (x as f64 * x_ratio).floor() as i32;
-            let py: i32 = (y as f64 * y_ratio).floor() as i32;
-            let pixel = src.get_pixel(px, py)?;
-
-            dest.set_pixel(x, y, &pixel)?;
+            let start = ((py * src_w + px) * 4) as usize;
+            pixel.copy_from_slice(&src.bytes[start..start + 4]);
         }
-    }
+    });
     src.width = dest.width;
     src.height = dest.height;
     src.bytes = dest.bytes;
@@ -72,11 +124,9 @@ fn bilinear_width(src: &mut Image, w2: i32) -> RasterResult<()> {
 
     let offset_x = (w2 / w1 / 2) as i32;
 
-    let x_start = 0 - offset_x;
-    let x_end = w2 - offset_x;
-
-    for y in 0..h1 {
-        for x in x_start..x_end {
+    image::fill_rows(&mut dest, |y, row| {
+        for (dest_x, pixel) in row.chunks_mut(4).enumerate() {
+            let x = dest_x as i32 - offset_x;
             let src_x = {
                 let src_x = x as f64 * x_ratio;
                 if src_x < 0.0 {
@@ -93,24 +143,14 @@ fn bilinear_width(src: &mut Image, w2: i32) -> RasterResult<()> {
             // limit range from 0 - 1
             let t_x = src_x - src_x_int as f64;
 
-            let src_color1 = src.get_pixel(src_x_int, y)?;
-            let src_color2 = src.get_pixel(src_x_int2, y)?;
-
-            // red
-            let red = _lerp(src_color1.r, src_color2.r, t_x);
+            let src_color1 = src_pixel(src, src_x_int, y);
+            let src_color2 = src_pixel(src, src_x_int2, y);
 
-            // green
-            let green = _lerp(src_color1.g, src_color2.g, t_x);
-
-            // blue
-            let blue = _lerp(src_color1.b, src_color2.b, t_x);
-
-            // alpha
-            let alpha = _lerp(src_color1.a, src_color2.a, t_x);
-
-            dest.set_pixel(x + offset_x, y, &Color::rgba(red, green, blue, alpha))?;
+            for c in 0..4 {
+                pixel[c] = _lerp(src_color1[c], src_color2[c], t_x);
+            }
         }
-    }
+    });
     src.width = dest.width;
     src.height = dest.height;
     src.bytes = dest.bytes;
@@ -129,45 +169,33 @@ fn bilinear_height(src: &mut Image, h2: i32) -> RasterResult<()> {
 
     let offset_y = (h2 / h1 / 2) as i32;
 
-    let y_start = 0 - offset_y;
-    let y_end = h2 - offset_y;
-
-    for x in 0..w1 {
-        for y in y_start..y_end {
-            let src_y = {
-                let src_y = y as f64 * y_ratio;
-                if src_y < 0.0 {
-                    0.0 // limit lower bound to 0
-                } else {
-                    src_y
-                }
-            };
-
-            let src_y_int = (src_y).floor() as i32;
+    image::fill_rows(&mut dest, |dest_y, row| {
+        let y = dest_y - offset_y;
+        let src_y = {
+            let src_y = y as f64 * y_ratio;
+            if src_y < 0.0 {
+                0.0 // limit lower bound to 0
+            } else {
+                src_y
+            }
+        };
 
-            let src_y_int2 = cmp::min(src_y_int + 1, h1 - 1); // limit range within $h1-1
+        let src_y_int = (src_y).floor() as i32;
 
-            // limit range from 0 - 1
-            let t_y = src_y - src_y_int as f64;
+        let src_y_int2 = cmp::min(src_y_int + 1, h1 - 1); // limit range within $h1-1
 
-            let src_color1 = src.get_pixel(x, src_y_int)?;
-            let src_color2 = src.get_pixel(x, src_y_int2)?;
+        // limit range from 0 - 1
+        let t_y = src_y - src_y_int as f64;
 
-            // red
-            let red = _lerp(src_color1.r, src_color2.r, t_y);
+        for (x, pixel) in row.chunks_mut(4).enumerate() {
+            let src_color1 = src_pixel(src, x as i32, src_y_int);
+            let src_color2 = src_pixel(src, x as i32, src_y_int2);
 
-            // green
-            let green = _lerp(src_color1.g, src_color2.g, t_y);
-
-            // blue
-            let blue = _lerp(src_color1.b, src_color2.b, t_y);
-
-            // alpha
-            let alpha = _lerp(src_color1.a, src_color2.a, t_y);
-
-            dest.set_pixel(x, y + offset_y, &Color::rgba(red, green, blue, alpha))?;
+            for c in 0..4 {
+                pixel[c] = _lerp(src_color1[c], src_color2[c], t_y);
+            }
         }
-    }
+    });
     src.width = dest.width;
     src.height = dest.height;
     src.bytes = dest.bytes;
@@ -175,6 +203,68 @@ fn bilinear_height(src: &mut Image, h2: i32) -> RasterResult<()> {
     Ok(())
 }
 
+// The RGBA bytes of a pixel. The caller makes sure x and y are within the image.
+fn src_pixel(src: &Image, x: i32, y: i32) -> &[u8] {
+    let start = ((y * src.width + x) * 4) as usize;
+    &src.bytes[start..start + 4]
+}
+
+// Round and clamp a channel value to 0 - 255. Lanczos weights can be negative, so sums can
+// overshoot.
+fn clamp_channel(value: f32) -> u8 {
+    if value < 0.0 {
+        0
+    } else if value > 255.0 {
+        255
+    } else {
+        value.round() as u8
+    }
+}
+
+// For every output pixel along one axis, the source pixels it samples and their normalized
+// weights. Samples past the edges are clamped to the nearest edge pixel. An empty source has
+// nothing to sample so the output pixels are left transparent.
+fn lanczos_weights(src_len: i32, dest_len: i32) -> Vec<Vec<(i32, f32)>> {
+    if src_len <= 0 {
+        return (0..dest_len).map(|_| Vec::new()).collect();
+    }
+
+    let ratio = src_len as f32 / dest_len as f32;
+    let scale = if ratio > 1.0 { ratio } else { 1.0 };
+    let support = 3.0 * scale;
+
+    (0..dest_len)
+        .map(|i| {
+            let center = (i as f32 + 0.5) * ratio - 0.5;
+            let start = (center - support).ceil() as i32;
+            let end = (center + support).floor() as i32;
+            let mut weights: Vec<(i32, f32)> = (start..end + 1)
+                .map(|j| {
+                    let index = cmp::max(0, cmp::min(src_len - 1, j));
+                    (index, lanczos((j as f32 - center) / scale))
+                })
+                .collect();
+            let total: f32 = weights.iter().map(|&(_, weight)| weight).sum();
+            for weight in &mut weights {
+                weight.1 /= total;
+            }
+            weights
+        })
+        .collect()
+}
+
+// The Lanczos kernel with a radius of 3.
+fn lanczos(t: f32) -> f32 {
+    if t == 0.0 {
+        1.0
+    } else if t.abs() < 3.0 {
+        let pi_t = f32::consts::PI * t;
+        3.0 * pi_t.sin() * (pi_t / 3.0).sin() / (pi_t * pi_t)
+    } else {
+        0.0
+    }
+}
+
 // Simple linear function
 fn _lerp(a: u8, b: u8, t: f64) -> u8 {
     let a = a as f64;
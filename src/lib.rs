@@ -2,7 +2,7 @@
 //!
 //! Raster is an image processing lib for Rust.
 //!
-//! It provides a simplified API for processing raster images (JPEG, PNG and GIF).
+//! It provides a simplified API for processing raster images (JPEG, PNG, GIF, BMP and WebP).
 //!
 //! ## Installation
 //! Add this to your Cargo.toml file:
@@ -71,6 +71,14 @@
 //!
 //! More modes available, see the resize API.
 //!
+//! Resizing and blending can use all CPU cores by enabling the optional `rayon` feature:
+//!
+//! ```rust,ignore
+//! [dependencies]
+//!
+//! raster = { version = "x.x.x", features = ["rayon"] }
+//! ```
+//!
 //! ## Rotating Images
 //!
 //! Images can be rotated both clockwise and counter-clockwise at any arbitrary angle with a
@@ -90,44 +98,59 @@ pub mod editor;
 pub mod error;
 pub mod filter;
 pub mod interpolate;
+pub mod pipeline;
 pub mod transform;
 mod blend;
 mod color;
 mod endec;
+mod exif;
 mod image;
+mod palette;
 mod position;
 
 // crates
+extern crate deflate;
 extern crate gif;
 extern crate image as piston_image;
 extern crate png;
+#[cfg(feature = "rayon")]
+extern crate rayon;
 
 // from rust
 use std::ascii::AsciiExt;
-use std::fs::File;
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
 use std::path::Path;
 
 // from external crate
-use piston_image::GenericImage;
 
 // from local crate
-use error::{RasterError, RasterResult};
+use error::RasterResult;
 
 // re-exports
 pub use blend::BlendMode;
 pub use color::Color;
+pub use editor::GradientMode;
 pub use editor::ResizeMode;
+pub use endec::PngCompression;
+pub use exif::Metadata;
 pub use filter::BlurMode;
 pub use filter::Orientation;
 pub use image::Histogram;
 pub use image::Image;
 pub use image::ImageFormat;
 pub use interpolate::InterpolationMode;
+pub use pipeline::Pipeline;
 pub use position::PositionMode;
 pub use transform::TransformMode;
 
 /// Create an image from an image file.
 ///
+/// JPEG images are flipped and rotated according to their EXIF orientation so that they are
+/// upright. Use `open_no_rotate` to get the pixels as they are stored.
+///
+/// Note: WebP images can be opened but not saved.
+///
 /// # Errors
 ///
 /// This function can return `RasterError::Io`, `RasterError::Decode`, or
@@ -142,6 +165,30 @@ pub use transform::TransformMode;
 /// println!("{:?}", image.bytes);
 /// ```
 pub fn open(image_file: &str) -> RasterResult<Image> {
+    read_image(image_file, true)
+}
+
+/// Create a list of images from the frames of an animated GIF.
+///
+/// Each frame is composited onto the full canvas, honoring the disposal method of the frame
+/// before it, so every image has the dimensions of the GIF. Other formats and still GIFs return a
+/// single image.
+///
+/// # Errors
+///
+/// This function can return `RasterError::Io`, `RasterError::Decode`, or
+/// `RasterError::UnsupportedFormat` upon failure.
+/// See error module for more info.
+///
+/// # Examples
+///
+/// ```
+/// let frames = raster::open_frames("tests/in/animated.gif").unwrap();
+/// for (i, frame) in frames.iter().enumerate() {
+///     raster::save(frame, &format!("tests/out/test_open_frames_{}.png", i)).unwrap();
+/// }
+/// ```
+pub fn open_frames(image_file: &str) -> RasterResult<Vec<Image>> {
     let path = Path::new(image_file);
     let ext = path.extension()
         .and_then(|s| s.to_str())
@@ -150,32 +197,136 @@ pub fn open(image_file: &str) -> RasterResult<Image> {
     // Open the file with basic error check
     let file = File::open(image_file)?;
 
-    match &ext[..] {
-        "gif" => Ok(endec::decode_gif(&file)?),
-        "jpg" | "jpeg" => {
-            let src = piston_image::open(image_file)?;
-            let (w, h) = src.dimensions();
-            let mut bytes = Vec::with_capacity((w * h) as usize * 4);
-            for y in 0..h {
-                for x in 0..w {
-                    let p = src.get_pixel(x, y);
-                    bytes.extend_from_slice(&p.data[0..4]);
-                }
+    match ext.parse()? {
+        ImageFormat::Gif => endec::decode_gif_frames(BufReader::new(file)),
+        format => Ok(vec![endec::decode(BufReader::new(file), format)?]),
+    }
+}
+
+/// Create an image from the bytes of an encoded image, eg. an image received over the network.
+/// The image format is detected from the first bytes of the data.
+///
+/// JPEG images are flipped and rotated according to their EXIF orientation, like `open` does.
+///
+/// # Errors
+///
+/// This function can return `RasterError::Decode` or `RasterError::UnsupportedFormat` upon
+/// failure.
+/// See error module for more info.
+///
+/// # Examples
+///
+/// ```
+/// use std::fs;
+///
+/// let data = fs::read("tests/in/sample.png").unwrap();
+/// let image = raster::open_from_bytes(&data).unwrap();
+/// assert_eq!(image.width, 500);
+/// ```
+pub fn open_from_bytes(data: &[u8]) -> RasterResult<Image> {
+    decode_image(data, endec::detect_format(data)?, true)
+}
+
+/// Create an image from an image file, ignoring the EXIF orientation of JPEG images.
+///
+/// # Errors
+///
+/// This function can return `RasterError::Io`, `RasterError::Decode`, or
+/// `RasterError::UnsupportedFormat` upon failure.
+/// See error module for more info.
+///
+/// # Examples
+///
+/// ```
+/// // Tagged to be rotated 90 degrees clockwise
+/// let image = raster::open_no_rotate("tests/in/exif-orientation-6.jpg").unwrap();
+/// assert_eq!((40, 20), (image.width, image.height));
+/// ```
+pub fn open_no_rotate(image_file: &str) -> RasterResult<Image> {
+    read_image(image_file, false)
+}
+
+/// Read the capture metadata of an image file without decoding its pixels.
+///
+/// Only the EXIF segment of JPEG images is read. Other formats, and JPEG images without EXIF,
+/// return a `Metadata` with every field set to `None`.
+///
+/// # Errors
+///
+/// This function can return `RasterError::Io` upon failure.
+/// See error module for more info.
+///
+/// # Examples
+///
+/// ```
+/// let metadata = raster::read_metadata("tests/in/exif-orientation-6.jpg").unwrap();
+/// assert_eq!(Some("RC-1000".to_string()), metadata.model);
+/// ```
+pub fn read_metadata(image_file: &str) -> RasterResult<Metadata> {
+    let mut data = Vec::new();
+    File::open(image_file)?.read_to_end(&mut data)?;
+    Ok(exif::metadata(&data))
+}
+
+/// Resize an image file and save it to another file, without decoding the whole source image
+/// into memory when possible.
+///
+/// Non-interlaced PNG sources that are made smaller are decoded a row at a time, and each output
+/// pixel is the average of the source pixels it covers. This allows shrinking images that are too
+/// large to open. Other sources are opened and resized with `editor::resize` as usual, so the
+/// result can differ slightly from the streamed path.
+///
+/// # Errors
+///
+/// This function can return the errors of `open`, `editor::resize` and `save`.
+/// See error module for more info.
+///
+/// # Examples
+///
+/// ```
+/// use raster::ResizeMode;
+///
+/// let out = "tests/out/test_resize_file.png";
+/// raster::resize_file("tests/in/sample.png", out, 200, 200, ResizeMode::Fit).unwrap();
+/// ```
+pub fn resize_file(
+    input: &str,
+    output: &str,
+    w: i32,
+    h: i32,
+    mode: ResizeMode,
+) -> RasterResult<()> {
+    let format: ImageFormat = Path::new(input)
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .parse()?;
+
+    if format == ImageFormat::Png {
+        let file = BufReader::new(File::open(input)?);
+        let streamed = endec::decode_png_downscaled(file, |width, height| {
+            editor::resize_dimensions(width, height, w, h, &mode)
+        })?;
+        if let Some(mut image) = streamed {
+            if mode == ResizeMode::Fill {
+                editor::crop(&mut image, w, h, PositionMode::Center, 0, 0)?;
             }
-            Ok(Image {
-                width: w as i32,
-                height: h as i32,
-                bytes: bytes,
-            })
+            return save(&image, output);
         }
-        "png" => Ok(endec::decode_png(&file)?),
-        _ => Err(RasterError::UnsupportedFormat(ext)),
     }
+
+    let mut image = open(input)?;
+    editor::resize(&mut image, w, h, mode)?;
+    save(&image, output)
 }
 
 /// Save an image to an image file. The image type is detected from the file extension of the file
 /// name.
 ///
+/// An `Image` holds only pixels, so no metadata of the source file is ever written. EXIF data,
+/// including GPS positions, is dropped when an image is opened and saved again. This applies to
+/// all the save functions.
+///
 /// # Errors
 ///
 /// This function can return `RasterError::Io`, `RasterError::Encode`, or
@@ -190,23 +341,183 @@ pub fn open(image_file: &str) -> RasterResult<Image> {
 /// raster::save(&image, "tests/out/test.png").unwrap();
 /// ```
 pub fn save(image: &Image, out: &str) -> RasterResult<()> {
+    save_with_quality(image, out, 75)
+}
+
+/// Save an image to a file in the given format, regardless of the file extension. JPEG is encoded
+/// with the same quality as `save`.
+///
+/// # Errors
+///
+/// This function can return `RasterError::Io`, `RasterError::Encode`, or
+/// `RasterError::UnsupportedFormat` upon failure.
+/// See error module for more info.
+///
+/// # Examples
+///
+/// ```
+/// use raster::ImageFormat;
+///
+/// let image = raster::open("tests/in/sample.png").unwrap();
+/// raster::save_as(&image, "tests/out/test_save_as.tmp", ImageFormat::Png).unwrap();
+/// ```
+pub fn save_as(image: &Image, out: &str, format: ImageFormat) -> RasterResult<()> {
+    // Encode before creating the file, so an existing file is left as is when encoding fails
+    let mut bytes = Vec::new();
+    endec::encode(image, &mut bytes, format, 75)?;
+    Ok(fs::write(out, bytes)?)
+}
+
+/// Save a list of images as an animated GIF that loops forever.
+///
+/// The delay between frames is in milliseconds. GIF stores delays in hundredths of a second so it
+/// is rounded to the nearest 10 ms.
+///
+/// # Errors
+///
+/// All frames must have the same dimensions as the first one, otherwise this fails with
+/// `RasterError::DimensionMismatch`. An empty list fails with `RasterError::Encode`. This
+/// function can also return `RasterError::Io` upon failure.
+/// See error module for more info.
+///
+/// # Examples
+///
+/// ```
+/// use raster::{editor, Color, Image};
+///
+/// let mut frames = Vec::new();
+/// for color in vec![Color::red(), Color::green(), Color::blue()] {
+///     let mut frame = Image::blank(20, 20);
+///     editor::fill(&mut frame, color).unwrap();
+///     frames.push(frame);
+/// }
+/// raster::save_frames(&frames, "tests/out/test_save_frames.gif", 500).unwrap();
+/// ```
+pub fn save_frames(frames: &[Image], out: &str, delay_ms: u16) -> RasterResult<()> {
+    if let Some(first) = frames.first() {
+        for frame in frames {
+            image::check_dimensions(first, frame)?;
+        }
+    }
+
+    let delay = ((delay_ms as u32 + 5) / 10) as u16;
+
+    // Encode before creating the file, so an existing file is left as is when encoding fails
+    let mut bytes = Vec::new();
+    endec::encode_gif_frames(frames, &mut bytes, delay)?;
+    Ok(fs::write(out, bytes)?)
+}
+
+/// Save an image as PNG with the given compression level, regardless of the file extension.
+///
+/// `save` uses `PngCompression::Fast`. Better compression makes smaller files, which suits the
+/// web, but takes longer. The decoded pixels are the same for every level.
+///
+/// # Errors
+///
+/// This function can return `RasterError::Io` or `RasterError::Encode` upon failure.
+/// See error module for more info.
+///
+/// # Examples
+///
+/// ```
+/// use raster::PngCompression;
+///
+/// let image = raster::open("tests/in/sample.png").unwrap();
+/// raster::save_png(&image, "tests/out/test_save_png.png", PngCompression::Best).unwrap();
+/// ```
+pub fn save_png(image: &Image, out: &str, compression: PngCompression) -> RasterResult<()> {
+    // Encode before creating the file, so an existing file is left as is when encoding fails
+    let mut bytes = Vec::new();
+    endec::encode_png_with(image, &mut bytes, compression)?;
+    Ok(fs::write(out, bytes)?)
+}
+
+/// Encode an image into a byte buffer instead of a file. JPEG is encoded with the same quality as
+/// `save`.
+///
+/// # Errors
+///
+/// This function can return `RasterError::Io` or `RasterError::Encode` upon failure.
+/// See error module for more info.
+///
+/// # Examples
+///
+/// ```
+/// use raster::ImageFormat;
+///
+/// let image = raster::open("tests/in/sample.png").unwrap();
+/// let bytes = raster::save_to_bytes(&image, ImageFormat::Png).unwrap();
+///
+/// // Formats can also be given by name
+/// let format = "jpg".parse().unwrap();
+/// let bytes = raster::save_to_bytes(&image, format).unwrap();
+/// ```
+pub fn save_to_bytes(image: &Image, format: ImageFormat) -> RasterResult<Vec<u8>> {
+    let mut bytes = Vec::new();
+    endec::encode(image, &mut bytes, format, 75)?;
+    Ok(bytes)
+}
+
+/// Save an image to an image file with the given JPEG quality. The image type is detected from
+/// the file extension of the file name.
+///
+/// Quality is from 1 - 100 where higher means better quality and bigger files. Values outside that
+/// range are clamped. The quality only applies to JPEG, other formats are saved as with `save`,
+/// which uses a quality of 75.
+///
+/// # Errors
+///
+/// This function can return `RasterError::Io`, `RasterError::Encode`, or
+/// `RasterError::UnsupportedFormat` upon failure.
+/// See error module for more info.
+///
+/// # Examples
+///
+/// ```
+/// // Create an image from file
+/// let image = raster::open("tests/in/sample.jpg").unwrap();
+/// raster::save_with_quality(&image, "tests/out/test_quality.jpg", 90).unwrap();
+/// ```
+pub fn save_with_quality(image: &Image, out: &str, quality: u8) -> RasterResult<()> {
     let path = Path::new(out);
     let ext = path.extension()
         .and_then(|s| s.to_str())
         .map_or("".to_string(), |s| s.to_ascii_lowercase());
+    let format = ext.parse()?;
+
+    // Encode before creating the file, so an existing file is left as is when encoding fails
+    let mut bytes = Vec::new();
+    endec::encode(image, &mut bytes, format, quality.clamp(1, 100))?;
+    Ok(fs::write(path, bytes)?)
+}
+
+// Private functions
+
+// Decode the bytes of an image, optionally applying the EXIF orientation of JPEG images.
+fn decode_image(data: &[u8], format: ImageFormat, orient: bool) -> RasterResult<Image> {
+    let is_jpeg = format == ImageFormat::Jpeg;
+    let mut image = endec::decode(data, format)?;
 
-    match &ext[..] {
-        "gif" => Ok(endec::encode_gif(&image, &path)?),
-        "jpg" | "jpeg" => {
-            piston_image::save_buffer(
-                &path,
-                &image.bytes,
-                image.width as u32,
-                image.height as u32,
-                piston_image::RGBA(8),
-            ).map_err(|_| RasterError::Encode(ImageFormat::Jpeg, "Format".to_string()))
+    if orient && is_jpeg {
+        if let Some(orientation) = exif::metadata(data).orientation {
+            exif::orient(&mut image, orientation)?;
         }
-        "png" => Ok(endec::encode_png(&image, &path)?),
-        _ => Err(RasterError::UnsupportedFormat(ext)),
     }
+
+    Ok(image)
+}
+
+// Decode an image file, optionally applying the EXIF orientation of JPEG images.
+fn read_image(image_file: &str, orient: bool) -> RasterResult<Image> {
+    let path = Path::new(image_file);
+    let ext = path.extension()
+        .and_then(|s| s.to_str())
+        .map_or("".to_string(), |s| s.to_ascii_lowercase());
+
+    // Open the file with basic error check
+    let mut data = Vec::new();
+    File::open(image_file)?.read_to_end(&mut data)?;
+
+    decode_image(&data, ext.parse()?, orient)
 }
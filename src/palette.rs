@@ -0,0 +1,127 @@
+//!  A module for building color palettes using median cut.
+
+// from rust
+use std::cmp;
+
+// from external crate
+
+// from local crate
+use Color;
+use Image;
+
+// Most pixels sampled from an image. Larger images are sampled on a grid.
+const MAX_SAMPLES: i32 = 10000;
+
+// Reduce the colors of an image to at most count colors using median cut. Returns the average
+// color of each box and the number of sampled pixels in it, most popular first. Alpha is ignored.
+pub fn median_cut(image: &Image, count: usize) -> Vec<(Color, usize)> {
+    let step = cmp::max(
+        1,
+        ((image.width * image.height) as f32 / MAX_SAMPLES as f32)
+            .sqrt()
+            .ceil() as i32,
+    );
+    let mut pixels = Vec::new();
+    for y in (0..image.height).step_by(step as usize) {
+        for x in (0..image.width).step_by(step as usize) {
+            let start = ((y * image.width + x) * 4) as usize;
+            pixels.push([
+                image.bytes[start],
+                image.bytes[start + 1],
+                image.bytes[start + 2],
+            ]);
+        }
+    }
+
+    let mut boxes = if pixels.is_empty() || count == 0 {
+        Vec::new()
+    } else {
+        vec![pixels]
+    };
+
+    // Split the box with the widest channel range at the median of that channel
+    while boxes.len() < count {
+        let widest = boxes
+            .iter()
+            .enumerate()
+            .map(|(i, pixels)| (i, widest_channel(pixels)))
+            .max_by_key(|&(_, (_, range))| range);
+        let (index, channel) = match widest {
+            Some((index, (channel, range))) if range > 0 => (index, channel),
+            _ => break,
+        };
+
+        let mut pixels = boxes.swap_remove(index);
+        pixels.sort_by_key(|p| p[channel]);
+        let split = split_index(&pixels, channel);
+        let upper = pixels.split_off(split);
+        boxes.push(pixels);
+        boxes.push(upper);
+    }
+
+    let mut palette: Vec<(Color, usize)> = boxes
+        .iter()
+        .map(|pixels| (average(pixels), pixels.len()))
+        .collect();
+    palette.sort_by_key(|&(_, population)| cmp::Reverse(population));
+    palette
+}
+
+// Index of the palette color closest to r, g and b.
+pub fn nearest(palette: &[Color], r: f32, g: f32, b: f32) -> usize {
+    let mut nearest = 0;
+    let mut nearest_distance = f32::MAX;
+    for (i, color) in palette.iter().enumerate() {
+        let dr = color.r as f32 - r;
+        let dg = color.g as f32 - g;
+        let db = color.b as f32 - b;
+        let distance = dr * dr + dg * dg + db * db;
+        if distance < nearest_distance {
+            nearest = i;
+            nearest_distance = distance;
+        }
+    }
+    nearest
+}
+
+// Private functions
+
+fn average(pixels: &[[u8; 3]]) -> Color {
+    let mut sum = [0u64; 3];
+    for p in pixels {
+        for (total, &value) in sum.iter_mut().zip(p) {
+            *total += value as u64;
+        }
+    }
+    let len = pixels.len() as u64;
+    let mean = |total: u64| ((total + len / 2) / len) as u8;
+    Color::rgb(mean(sum[0]), mean(sum[1]), mean(sum[2]))
+}
+
+// Index of the change in value of the sorted channel closest to the median, so that pixels with
+// the same value end up in the same box.
+fn split_index(pixels: &[[u8; 3]], channel: usize) -> usize {
+    let median = pixels.len() / 2;
+    let changes = |i: &usize| *i > 0 && pixels[*i - 1][channel] != pixels[*i][channel];
+    let below = (1..median + 1).rev().find(changes);
+    let above = (median + 1..pixels.len()).find(changes);
+    match (below, above) {
+        (Some(below), Some(above)) if above - median < median - below => above,
+        (Some(below), _) => below,
+        (None, Some(above)) => above,
+        (None, None) => median,
+    }
+}
+
+// The channel with the largest range of values and that range.
+fn widest_channel(pixels: &[[u8; 3]]) -> (usize, u8) {
+    let mut widest = (0, 0);
+    for c in 0..3 {
+        let min = pixels.iter().map(|p| p[c]).min().unwrap_or(0);
+        let max = pixels.iter().map(|p| p[c]).max().unwrap_or(0);
+        if max - min > widest.1 {
+            widest = (c, max - min);
+        }
+    }
+    widest
+}
@@ -0,0 +1,141 @@
+//!  A module for chaining operations on an image.
+//!
+//! A `Pipeline` wraps an image and has a method for common operations in the editor, filter and
+//! transform modules. Each method changes the image in place and returns the pipeline, so
+//! operations can be chained with `?`.
+//!
+//! # Examples
+//!
+//! ```
+//! use raster::{Pipeline, ResizeMode};
+//!
+//! # fn run() -> raster::error::RasterResult<()> {
+//! Pipeline::open("tests/in/sample.jpg")?
+//!     .resize(200, 200, ResizeMode::Fit)?
+//!     .grayscale()?
+//!     .save("tests/out/test_pipeline.jpg")?;
+//! # Ok(())
+//! # }
+//! # run().unwrap();
+//! ```
+
+// from rust
+
+// from external crate
+
+// from local crate
+use error::RasterResult;
+use editor::{self, ResizeMode};
+use filter::{self, BlurMode};
+use position::PositionMode;
+use transform::{self, TransformMode};
+use Color;
+use Image;
+
+/// A struct for chaining operations on an image.
+#[derive(Debug, Clone)]
+pub struct Pipeline {
+    image: Image,
+}
+
+impl Pipeline {
+    /// Start a pipeline with an image.
+    pub fn new(image: Image) -> Pipeline {
+        Pipeline { image }
+    }
+
+    /// Start a pipeline with an image file, see `raster::open`.
+    pub fn open(image_file: &str) -> RasterResult<Pipeline> {
+        ::open(image_file).map(Pipeline::new)
+    }
+
+    /// Get the current image.
+    pub fn image(&self) -> &Image {
+        &self.image
+    }
+
+    /// Stop the pipeline and get the image.
+    pub fn into_image(self) -> Image {
+        self.image
+    }
+
+    /// Save the current image, see `raster::save`. The pipeline can continue afterwards.
+    pub fn save(&mut self, out: &str) -> RasterResult<&mut Pipeline> {
+        ::save(&self.image, out)?;
+        Ok(self)
+    }
+
+    /// See `filter::blur`.
+    pub fn blur(&mut self, mode: BlurMode) -> RasterResult<&mut Pipeline> {
+        filter::blur(&mut self.image, mode)?;
+        Ok(self)
+    }
+
+    /// See `filter::brightness`.
+    pub fn brightness(&mut self, factor: f32) -> RasterResult<&mut Pipeline> {
+        filter::brightness(&mut self.image, factor)?;
+        Ok(self)
+    }
+
+    /// See `filter::contrast`.
+    pub fn contrast(&mut self, factor: f32) -> RasterResult<&mut Pipeline> {
+        filter::contrast(&mut self.image, factor)?;
+        Ok(self)
+    }
+
+    /// See `editor::crop`.
+    pub fn crop(
+        &mut self,
+        crop_width: i32,
+        crop_height: i32,
+        position: PositionMode,
+        offset_x: i32,
+        offset_y: i32,
+    ) -> RasterResult<&mut Pipeline> {
+        editor::crop(
+            &mut self.image,
+            crop_width,
+            crop_height,
+            position,
+            offset_x,
+            offset_y,
+        )?;
+        Ok(self)
+    }
+
+    /// See `editor::fill`.
+    pub fn fill(&mut self, color: Color) -> RasterResult<&mut Pipeline> {
+        editor::fill(&mut self.image, color)?;
+        Ok(self)
+    }
+
+    /// See `transform::flip`.
+    pub fn flip(&mut self, mode: TransformMode) -> RasterResult<&mut Pipeline> {
+        transform::flip(&mut self.image, mode)?;
+        Ok(self)
+    }
+
+    /// See `filter::grayscale`.
+    pub fn grayscale(&mut self) -> RasterResult<&mut Pipeline> {
+        filter::grayscale(&mut self.image)?;
+        Ok(self)
+    }
+
+    /// See `editor::resize`.
+    pub fn resize(&mut self, w: i32, h: i32, mode: ResizeMode) -> RasterResult<&mut Pipeline> {
+        editor::resize(&mut self.image, w, h, mode)?;
+        Ok(self)
+    }
+
+    /// See `transform::rotate`.
+    pub fn rotate(&mut self, degree: f32, bg: Color) -> RasterResult<&mut Pipeline> {
+        transform::rotate(&mut self.image, degree, bg)?;
+        Ok(self)
+    }
+
+    /// See `filter::sharpen`.
+    pub fn sharpen(&mut self) -> RasterResult<&mut Pipeline> {
+        filter::sharpen(&mut self.image)?;
+        Ok(self)
+    }
+}
@@ -19,6 +19,13 @@ pub enum PositionMode {
     BottomLeft,
     BottomCenter,
     BottomRight,
+    /// Percentages of the free space across and down, clamped to 0.0 - 100.0. The image is
+    /// anchored the same way, so `Percent(0.0, 0.0)` is `TopLeft`, `Percent(50.0, 50.0)` is
+    /// `Center` and `Percent(100.0, 100.0)` is `BottomRight`.
+    Percent(f32, f32),
+    /// Exact x and y coordinates of the top left corner of the image on the canvas. Any offsets
+    /// are added on top.
+    Absolute(i32, i32),
 }
 
 /// Struct for computing position on an image.
@@ -87,6 +94,19 @@ impl Position {
                 let y = (canvas_height - image_height) + offset_y;
                 (x, y)
             }
+            PositionMode::Percent(percent_x, percent_y) => {
+                let x = percent_of(canvas_width - image_width, percent_x) + offset_x;
+                let y = percent_of(canvas_height - image_height, percent_y) + offset_y;
+                (x, y)
+            }
+            PositionMode::Absolute(x, y) => (x + offset_x, y + offset_y),
         })
     }
 }
+
+// Private functions
+
+// Get a percentage of a length, clamping the percentage to 0.0 - 100.0.
+fn percent_of(length: i32, percent: f32) -> i32 {
+    (length as f32 * percent.clamp(0.0, 100.0) / 100.0).round() as i32
+}
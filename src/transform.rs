@@ -2,19 +2,32 @@
 
 // from rust
 use std::cmp;
+use std::f32;
+use std::fmt;
+use std::str::FromStr;
 
 // from external crate
 
 // from local crate
-use error::RasterResult;
+use error::{RasterError, RasterResult};
 use Image;
 use Color;
-use interpolate::{resample, InterpolationMode};
-use position::PositionMode;
-use editor::crop;
+use interpolate::InterpolationMode;
+use editor::{resize_with, ResizeMode};
 
 /// An enum for the various modes that can be used for transforming.
-#[derive(Debug)]
+///
+/// Transform modes can also be parsed from their lowercase names.
+///
+/// # Examples
+/// ```
+/// use raster::TransformMode;
+///
+/// let mode: TransformMode = "vertical".parse().unwrap();
+/// assert_eq!(TransformMode::Vertical, mode);
+/// assert_eq!("vertical", mode.to_string());
+/// ```
+#[derive(Debug, PartialEq)]
 pub enum TransformMode {
     /// Transform on x axis.
     Horizontal,
@@ -22,6 +35,107 @@ pub enum TransformMode {
     Vertical,
 }
 
+/// Parse a transform mode from its name, "horizontal" or "vertical".
+///
+/// # Errors
+///
+/// Unknown names fail with `RasterError::InvalidTransformMode`.
+impl FromStr for TransformMode {
+    type Err = RasterError;
+
+    fn from_str(s: &str) -> RasterResult<TransformMode> {
+        match s {
+            "horizontal" => Ok(TransformMode::Horizontal),
+            "vertical" => Ok(TransformMode::Vertical),
+            _ => Err(RasterError::InvalidTransformMode(s.to_string())),
+        }
+    }
+}
+
+/// Display the transform mode using the same name accepted by `from_str`.
+impl fmt::Display for TransformMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            TransformMode::Horizontal => "horizontal",
+            TransformMode::Vertical => "vertical",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Apply an affine transformation, given as the 2x3 matrix `[a, b, c, d, e, f]` that maps each
+/// source position x, y to `a * x + b * y + c`, `d * x + e * y + f`.
+///
+/// The canvas grows to fit both the original area and the transformed image, so a translation
+/// shows as a shift and exposed areas are filled with the background color. Positions to the left
+/// of or above the original area move the whole result right or down. Pixels are sampled with
+/// bilinear interpolation. The identity matrix `[1.0, 0.0, 0.0, 0.0, 1.0, 0.0]` leaves the image
+/// unchanged. An empty image is left as is.
+///
+/// # Errors
+///
+/// If the matrix can't be inverted, eg. it scales by 0, this fails with
+/// `RasterError::InvalidMatrix`.
+///
+/// # Examples
+///
+/// ```
+/// use raster::{transform, Color};
+///
+/// // Shear horizontally
+/// let mut image = raster::open("tests/in/sample.png").unwrap();
+/// transform::affine(&mut image, [1.0, 0.3, 0.0, 0.0, 1.0, 0.0], Color::white()).unwrap();
+/// raster::save(&image, "tests/out/test_transform_affine.png").unwrap();
+/// ```
+///
+/// ![](https://kosinix.github.io/raster/out/test_transform_affine.png)
+///
+pub fn affine(src: &mut Image, matrix: [f32; 6], background: Color) -> RasterResult<()> {
+    let [a, b, c, d, e, f] = matrix;
+    let det = a * e - b * d;
+    if det.abs() < 1e-6 || !det.is_finite() {
+        return Err(RasterError::InvalidMatrix);
+    }
+    if src.width == 0 || src.height == 0 {
+        return Ok(());
+    }
+
+    // Bounds of the transformed corners and the original area
+    let (w1, h1) = (src.width as f32, src.height as f32);
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (0.0f32, 0.0f32, w1, h1);
+    for &(x, y) in &[(0.0, 0.0), (w1, 0.0), (0.0, h1), (w1, h1)] {
+        let (tx, ty) = (a * x + b * y + c, d * x + e * y + f);
+        min_x = min_x.min(tx);
+        min_y = min_y.min(ty);
+        max_x = max_x.max(tx);
+        max_y = max_y.max(ty);
+    }
+    // Shave off float noise so exact bounds don't add a pixel
+    let (min_x, min_y) = ((min_x + 0.001).floor(), (min_y + 0.001).floor());
+    let w2 = ((max_x - 0.001).ceil() - min_x).max(1.0) as i32;
+    let h2 = ((max_y - 0.001).ceil() - min_y).max(1.0) as i32;
+    let mut dest = Image::blank(w2, h2);
+
+    for dest_y in 0..dest.height {
+        for dest_x in 0..dest.width {
+            // Map the center of the destination pixel back into the source
+            let x = dest_x as f32 + 0.5 + min_x - c;
+            let y = dest_y as f32 + 0.5 + min_y - f;
+            let src_x = (e * x - b * y) / det - 0.5;
+            let src_y = (a * y - d * x) / det - 0.5;
+
+            let pixel = sample_bilinear(src, src_x, src_y, &background)?;
+            dest.set_pixel(dest_x, dest_y, &pixel)?;
+        }
+    }
+
+    src.width = dest.width;
+    src.height = dest.height;
+    src.bytes = dest.bytes;
+
+    Ok(())
+}
+
 /// Flip an image on its x or y axis.
 ///
 /// # Examples
@@ -55,42 +169,28 @@ pub enum TransformMode {
 /// ![](https://kosinix.github.io/raster/out/test_transform_flip_y.png)
 ///
 pub fn flip(src: &mut Image, mode: TransformMode) -> RasterResult<()> {
-    let w: i32 = src.width;
-    let h: i32 = src.height;
+    let w = src.width as usize;
+    let h = src.height as usize;
+    let row_len = w * 4;
+    if row_len == 0 || h == 0 {
+        return Ok(());
+    }
 
     match mode {
         TransformMode::Horizontal => {
-            for x in 0..w {
-                let src_x = x;
-                let dest_x = w - x - 1;
-                if dest_x <= src_x {
-                    break;
-                }
-                for y in 0..h {
-                    let pixel_left = src.get_pixel(src_x, y)?;
-                    let pixel_right = src.get_pixel(dest_x, y)?;
-
-                    src.set_pixel(dest_x, y, &pixel_left)?;
-                    src.set_pixel(src_x, y, &pixel_right)?;
+            for row in src.bytes.chunks_mut(row_len) {
+                for x in 0..w / 2 {
+                    let (left, right) = row.split_at_mut((w - x - 1) * 4);
+                    left[x * 4..x * 4 + 4].swap_with_slice(&mut right[..4]);
                 }
             }
 
             Ok(())
         }
         TransformMode::Vertical => {
-            for y in 0..h {
-                let src_y = y;
-                let dest_y = h - y - 1;
-                if dest_y <= src_y {
-                    break;
-                }
-                for x in 0..w {
-                    let pixel_top = src.get_pixel(x, src_y)?;
-                    let pixel_bottom = src.get_pixel(x, dest_y)?;
-
-                    src.set_pixel(x, dest_y, &pixel_top)?;
-                    src.set_pixel(x, src_y, &pixel_bottom)?;
-                }
+            for y in 0..h / 2 {
+                let (top, bottom) = src.bytes.split_at_mut((h - y - 1) * row_len);
+                top[y * row_len..(y + 1) * row_len].swap_with_slice(&mut bottom[..row_len]);
             }
 
             Ok(())
@@ -98,12 +198,113 @@ pub fn flip(src: &mut Image, mode: TransformMode) -> RasterResult<()> {
     }
 }
 
-/// Rotate an image clockwise. Negate the degrees to do a counter-clockwise rotation. Background
-/// color can be any color.
+/// Warp an image so its corners land on the given positions, eg. to straighten a photo of a
+/// document.
+///
+/// The corners are the destination positions of the top left, top right, bottom right and
+/// bottom left corners of the image, in that order. Straight lines stay straight. The canvas is
+/// the bounding box of the corners, moved so that its top left is at 0, 0, and areas outside the
+/// warped image are filled with the background color. Pixels are sampled with bilinear
+/// interpolation.
+///
+/// # Errors
+///
+/// A warped image is always a convex quadrilateral with its corners in the same clockwise order
+/// as the image. If the corners are out of order, eg. mirrored or crossing over, make a concave
+/// shape or three of them are on a line, this fails with `RasterError::InvalidMatrix`.
+///
+/// # Examples
+///
+/// ```
+/// use raster::{transform, Color};
+///
+/// let mut image = raster::open("tests/in/sample.png").unwrap();
+/// let (w, h) = (image.width as f32, image.height as f32);
+/// let corners = [(40.0, 0.0), (w - 40.0, 0.0), (w, h), (0.0, h)];
+/// transform::perspective(&mut image, corners, Color::white()).unwrap();
+/// raster::save(&image, "tests/out/test_transform_perspective.png").unwrap();
+/// ```
+///
+/// ![](https://kosinix.github.io/raster/out/test_transform_perspective.png)
+///
+pub fn perspective(
+    src: &mut Image,
+    corners: [(f32, f32); 4],
+    background: Color,
+) -> RasterResult<()> {
+    let (w1, h1) = (src.width as f32, src.height as f32);
+    let min_x = corners.iter().fold(f32::INFINITY, |min, c| min.min(c.0));
+    let min_y = corners.iter().fold(f32::INFINITY, |min, c| min.min(c.1));
+    let max_x = corners.iter().fold(f32::NEG_INFINITY, |max, c| max.max(c.0));
+    let max_y = corners.iter().fold(f32::NEG_INFINITY, |max, c| max.max(c.1));
+    if !(min_x.is_finite() && min_y.is_finite() && max_x.is_finite() && max_y.is_finite()) {
+        return Err(RasterError::InvalidMatrix);
+    }
+
+    // Every turn from one edge to the next must be clockwise, like the corners of the image
+    for i in 0..4 {
+        let (a, b, c) = (corners[i], corners[(i + 1) % 4], corners[(i + 2) % 4]);
+        let cross = (b.0 as f64 - a.0 as f64) * (c.1 as f64 - b.1 as f64)
+            - (b.1 as f64 - a.1 as f64) * (c.0 as f64 - b.0 as f64);
+        if cross <= 0.0 {
+            return Err(RasterError::InvalidMatrix);
+        }
+    }
+
+    // Homography from the destination back to the source, relative to the top left of the canvas
+    let (min_x, min_y) = ((min_x + 0.001).floor(), (min_y + 0.001).floor());
+    let dest_corners: Vec<(f32, f32)> = corners
+        .iter()
+        .map(|c| (c.0 - min_x, c.1 - min_y))
+        .collect();
+    let src_corners = [(0.0, 0.0), (w1, 0.0), (w1, h1), (0.0, h1)];
+    let h = homography(&dest_corners, &src_corners).ok_or(RasterError::InvalidMatrix)?;
+
+    let w2 = ((max_x - 0.001).ceil() - min_x).max(1.0) as i32;
+    let h2 = ((max_y - 0.001).ceil() - min_y).max(1.0) as i32;
+    let mut dest = Image::blank(w2, h2);
+
+    // The homography is scaled so that z is 1 at the canvas origin, which may be past the
+    // horizon. The warped image is on the side of the horizon where z has the sign of a corner.
+    let (corner_x, corner_y) = (dest_corners[0].0 as f64, dest_corners[0].1 as f64);
+    let z_ref = h[6] * corner_x + h[7] * corner_y + 1.0;
+
+    for dest_y in 0..dest.height {
+        for dest_x in 0..dest.width {
+            // Map the center of the destination pixel back into the source
+            let x = dest_x as f64 + 0.5;
+            let y = dest_y as f64 + 0.5;
+            let z = h[6] * x + h[7] * y + 1.0;
+            let pixel = if z * z_ref > 0.0 {
+                let src_x = (h[0] * x + h[1] * y + h[2]) / z - 0.5;
+                let src_y = (h[3] * x + h[4] * y + h[5]) / z - 0.5;
+                sample_bilinear(src, src_x as f32, src_y as f32, &background)?
+            } else {
+                // Behind the horizon of the warp
+                background.clone()
+            };
+            dest.set_pixel(dest_x, dest_y, &pixel)?;
+        }
+    }
+
+    src.width = dest.width;
+    src.height = dest.height;
+    src.bytes = dest.bytes;
+
+    Ok(())
+}
+
+/// Rotate an image clockwise around its center. Negate the degrees to do a counter-clockwise
+/// rotation. Background color can be any color.
 ///
-/// Note: If you look closely, the quality for arbitrary angles is not very good due to the simple
-/// sampling algorithm. The 90, 180, and 270 angles looks fine because no pixels are lost. This
-/// will be fixed in the future with a better sampling algorithm.
+/// The canvas is enlarged to fit the rotated image and the exposed corners are filled with the
+/// background color. Pixels are sampled with bilinear interpolation for smooth edges. For exact
+/// rotation by multiples of 90 degrees, see `rotate90`, `rotate180` and `rotate270`. An empty
+/// image is left as is.
+///
+/// # Errors
+///
+/// If the degrees are infinite or NaN, this fails with `RasterError::InvalidAngle`.
 ///
 /// # Examples
 ///
@@ -115,7 +316,7 @@ pub fn flip(src: &mut Image, mode: TransformMode) -> RasterResult<()> {
 /// //...
 ///
 /// let mut image = raster::open("tests/in/sample.png").unwrap();
-/// transform::rotate(&mut image, 45, Color::rgb(0,0,0)).unwrap();
+/// transform::rotate(&mut image, 45.0, Color::rgb(0,0,0)).unwrap();
 /// raster::save(&image, "tests/out/test_transform_rotate_45.png").unwrap();
 /// ```
 ///
@@ -130,59 +331,42 @@ pub fn flip(src: &mut Image, mode: TransformMode) -> RasterResult<()> {
 /// //...
 ///
 /// let mut image = raster::open("tests/in/sample.png").unwrap();
-/// transform::rotate(&mut image, -45, Color::rgb(252,145,145)).unwrap();
+/// transform::rotate(&mut image, -45.0, Color::rgb(252,145,145)).unwrap();
 /// raster::save(&image, "tests/out/test_transform_rotate_45cc.png").unwrap();
 /// ```
 ///
 /// ![](https://kosinix.github.io/raster/out/test_transform_rotate_45cc.png)
 ///
-pub fn rotate(src: &mut Image, degree: i32, bg: Color) -> RasterResult<()> {
-    let w1 = src.width;
-    let h1 = src.height;
-
-    let degree = degree as f32; // convert to float
-
-    // Using screen coords system, top left is always at (0,0)
-    let mut min_x = 0;
-    let mut max_x = 0;
-    let mut min_y = 0;
-    let mut max_y = 0;
-
-    let top_right_1: (i32, i32) = (w1, 0);
-    let top_right_2: (i32, i32) = _rotate(top_right_1, degree);
-    min_x = cmp::min(min_x, top_right_2.0);
-    max_x = cmp::max(max_x, top_right_2.0);
-    min_y = cmp::min(min_y, top_right_2.1);
-    max_y = cmp::max(max_y, top_right_2.1);
-
-    let bottom_right_1: (i32, i32) = (w1, h1);
-    let bottom_right_2: (i32, i32) = _rotate(bottom_right_1, degree);
-    min_x = cmp::min(min_x, bottom_right_2.0);
-    max_x = cmp::max(max_x, bottom_right_2.0);
-    min_y = cmp::min(min_y, bottom_right_2.1);
-    max_y = cmp::max(max_y, bottom_right_2.1);
-
-    let bottom_left_1: (i32, i32) = (0, h1);
-    let bottom_left_2: (i32, i32) = _rotate(bottom_left_1, degree);
-    min_x = cmp::min(min_x, bottom_left_2.0);
-    max_x = cmp::max(max_x, bottom_left_2.0);
-    min_y = cmp::min(min_y, bottom_left_2.1);
-    max_y = cmp::max(max_y, bottom_left_2.1);
-
-    let w2 = ((min_x as f32).abs() + (max_x as f32).abs()) as i32 + 1;
-    let h2 = ((min_y as f32).abs() + (max_y as f32).abs()) as i32 + 1;
-    let mut dest = Image::blank(w2, h2);
+pub fn rotate(src: &mut Image, degree: f32, bg: Color) -> RasterResult<()> {
+    if !degree.is_finite() {
+        return Err(RasterError::InvalidAngle(degree));
+    }
+    if src.width == 0 || src.height == 0 {
+        return Ok(());
+    }
 
-    for (dest_y, y) in (0..).zip(min_y..max_y + 1) {
-        for (dest_x, x) in (0..).zip(min_x..max_x + 1) {
-            let point: (i32, i32) = _rotate((x, y), -degree);
+    let w1 = src.width as f32;
+    let h1 = src.height as f32;
 
-            if point.0 >= 0 && point.0 < w1 && point.1 >= 0 && point.1 < h1 {
-                let pixel = src.get_pixel(point.0, point.1)?;
-                dest.set_pixel(dest_x, dest_y, &pixel)?;
-            } else {
-                dest.set_pixel(dest_x, dest_y, &Color::rgba(bg.r, bg.g, bg.b, bg.a))?;
-            }
+    let radians = degree.to_radians();
+    let cos = radians.cos();
+    let sin = radians.sin();
+
+    // Bounding box of the rotated image. Shave off float noise so 90 degrees doesn't add a pixel.
+    let w2 = ((w1 * cos.abs() + h1 * sin.abs()) - 0.001).ceil().max(1.0);
+    let h2 = ((w1 * sin.abs() + h1 * cos.abs()) - 0.001).ceil().max(1.0);
+    let mut dest = Image::blank(w2 as i32, h2 as i32);
+
+    for dest_y in 0..dest.height {
+        for dest_x in 0..dest.width {
+            // Map the center of the destination pixel back into the source, relative to centers
+            let x = dest_x as f32 + 0.5 - w2 / 2.0;
+            let y = dest_y as f32 + 0.5 - h2 / 2.0;
+            let src_x = x * cos + y * sin + w1 / 2.0 - 0.5;
+            let src_y = -x * sin + y * cos + h1 / 2.0 - 0.5;
+
+            let pixel = sample_bilinear(src, src_x, src_y, &bg)?;
+            dest.set_pixel(dest_x, dest_y, &pixel)?;
         }
     }
 
@@ -193,93 +377,213 @@ pub fn rotate(src: &mut Image, degree: i32, bg: Color) -> RasterResult<()> {
     Ok(())
 }
 
+/// Rotate an image 180 degrees. This is an exact pixel permutation, no pixels are lost.
+///
+/// # Examples
+///
+/// ```
+/// use raster::transform;
+///
+/// //...
+///
+/// let mut image = raster::open("tests/in/sample.png").unwrap();
+/// transform::rotate180(&mut image).unwrap();
+/// raster::save(&image, "tests/out/test_transform_rotate180.png").unwrap();
+/// ```
+///
+/// ![](https://kosinix.github.io/raster/out/test_transform_rotate180.png)
+///
+pub fn rotate180(src: &mut Image) -> RasterResult<()> {
+    let len = src.bytes.len() / 4;
+    for i in 0..len / 2 {
+        let j = len - i - 1;
+        for c in 0..4 {
+            src.bytes.swap(i * 4 + c, j * 4 + c);
+        }
+    }
+
+    Ok(())
+}
+
+/// Rotate an image 270 degrees clockwise, i.e. 90 degrees counter-clockwise. The width and
+/// height are swapped. This is an exact pixel permutation, no pixels are lost.
+///
+/// # Examples
+///
+/// ```
+/// use raster::transform;
+///
+/// //...
+///
+/// let mut image = raster::open("tests/in/sample.png").unwrap();
+/// transform::rotate270(&mut image).unwrap();
+/// raster::save(&image, "tests/out/test_transform_rotate270.png").unwrap();
+/// ```
+///
+/// ![](https://kosinix.github.io/raster/out/test_transform_rotate270.png)
+///
+pub fn rotate270(src: &mut Image) -> RasterResult<()> {
+    let w = src.width as usize;
+    rotate_orthogonal(src, |x, y| (y, w - x - 1));
+    Ok(())
+}
+
+/// Rotate an image 90 degrees clockwise. The width and height are swapped. This is an exact
+/// pixel permutation, no pixels are lost.
+///
+/// # Examples
+///
+/// ```
+/// use raster::transform;
+///
+/// //...
+///
+/// let mut image = raster::open("tests/in/sample.png").unwrap();
+/// transform::rotate90(&mut image).unwrap();
+/// raster::save(&image, "tests/out/test_transform_rotate90.png").unwrap();
+/// ```
+///
+/// ![](https://kosinix.github.io/raster/out/test_transform_rotate90.png)
+///
+pub fn rotate90(src: &mut Image) -> RasterResult<()> {
+    let h = src.height as usize;
+    rotate_orthogonal(src, |x, y| (h - y - 1, x));
+    Ok(())
+}
+
 /// Resize image to exact dimensions ignoring aspect ratio.
 /// Useful if you want to force exact width and height.
 pub fn resize_exact(src: &mut Image, w: i32, h: i32) -> RasterResult<()> {
-    resample(src, w, h, InterpolationMode::Bicubic)
+    resize_with(src, w, h, ResizeMode::Exact, InterpolationMode::Bicubic)
 }
 
 /// Resize image to exact height. Width is auto calculated.
 /// Useful for creating row of images with the same height.
 pub fn resize_exact_height(src: &mut Image, h: i32) -> RasterResult<()> {
-    let width = src.width;
-    let height = src.height;
-    let ratio = width as f32 / height as f32;
-
-    let resize_height = h;
-    let resize_width = (h as f32 * ratio) as i32;
-
-    resample(src, resize_width, resize_height, InterpolationMode::Bicubic)
+    let w = src.width;
+    resize_with(src, w, h, ResizeMode::ExactHeight, InterpolationMode::Bicubic)
 }
 
 /// Resize image to exact width. Height is auto calculated.
 /// Useful for creating column of images with the same width.
 pub fn resize_exact_width(src: &mut Image, w: i32) -> RasterResult<()> {
-    let width = src.width;
-    let height = src.height;
-    let ratio = width as f32 / height as f32;
-
-    let resize_width = w;
-    let resize_height = (w as f32 / ratio).round() as i32;
-
-    resample(src, resize_width, resize_height, InterpolationMode::Bicubic)
+    let h = src.height;
+    resize_with(src, w, h, ResizeMode::ExactWidth, InterpolationMode::Bicubic)
 }
 
 /// Resize image to fill all the space in the given dimension. Excess parts are removed.
 pub fn resize_fill(src: &mut Image, w: i32, h: i32) -> RasterResult<()> {
-    let width = src.width;
-    let height = src.height;
-    let ratio = width as f32 / height as f32;
-
-    // Base optimum size on new width
-    let mut optimum_width = w;
-    let mut optimum_height = (w as f32 / ratio).round() as i32;
-
-    if (optimum_width < w) || (optimum_height < h) {
-        // Oops, where trying to fill and there are blank areas
-        // So base optimum size on height instead
-        optimum_width = (h as f32 * ratio) as i32;
-        optimum_height = h;
-    }
-
-    resample(
-        src,
-        optimum_width,
-        optimum_height,
-        InterpolationMode::Bicubic,
-    ).and_then(|_| crop(src, w, h, PositionMode::Center, 0, 0)) // Trim excess parts
+    resize_with(src, w, h, ResizeMode::Fill, InterpolationMode::Bicubic)
 }
 
 /// Resize an image to fit within the given width and height.
 /// The re-sized image will not exceed the given dimension.
 /// Preserves the aspect ratio.
 pub fn resize_fit(src: &mut Image, w: i32, h: i32) -> RasterResult<()> {
-    let ratio: f64 = src.width as f64 / src.height as f64;
+    resize_with(src, w, h, ResizeMode::Fit, InterpolationMode::Bicubic)
+}
 
-    // Try basing it on width first
-    let mut resize_width = w;
-    let mut resize_height = (w as f64 / ratio).round() as i32;
+// Private functions
+
+// The homography, as the first 8 values of a 3x3 matrix whose last value is 1, that maps each of
+// the 4 from points to the matching to point. None if it doesn't exist.
+fn homography(from: &[(f32, f32)], to: &[(f32, f32)]) -> Option<[f64; 8]> {
+    // Each pair of points gives two linear equations in the 8 unknowns, solved with Gaussian
+    // elimination
+    let mut rows = [[0.0f64; 9]; 8];
+    for (i, (&(x, y), &(u, v))) in from.iter().zip(to).enumerate() {
+        let (x, y, u, v) = (x as f64, y as f64, u as f64, v as f64);
+        rows[i * 2] = [x, y, 1.0, 0.0, 0.0, 0.0, -u * x, -u * y, u];
+        rows[i * 2 + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -v * x, -v * y, v];
+    }
 
-    if (resize_width > w) || (resize_height > h) {
-        // Oops, either width or height does not fit
-        // So base on height instead
-        resize_height = h;
-        resize_width = (h as f64 * ratio).round() as i32;
+    for col in 0..8 {
+        let pivot = (col..8).max_by(|&a, &b| {
+            rows[a][col]
+                .abs()
+                .partial_cmp(&rows[b][col].abs())
+                .unwrap_or(cmp::Ordering::Equal)
+        })?;
+        if rows[pivot][col].abs() < 1e-9 {
+            return None;
+        }
+        rows.swap(col, pivot);
+        let pivot_row = rows[col];
+        for (i, row) in rows.iter_mut().enumerate() {
+            if i != col {
+                let factor = row[col] / pivot_row[col];
+                for (value, pivot_value) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                    *value -= factor * pivot_value;
+                }
+            }
+        }
     }
 
-    resample(src, resize_width, resize_height, InterpolationMode::Bicubic)
+    let mut h = [0.0f64; 8];
+    for (i, value) in h.iter_mut().enumerate() {
+        *value = rows[i][8] / rows[i][i];
+    }
+    Some(h)
 }
 
-// Private functions
+// Sample a pixel at a fractional position. Neighbors outside the image take the background color.
+fn sample_bilinear(src: &Image, x: f32, y: f32, bg: &Color) -> RasterResult<Color> {
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let x_diff = x - x0;
+    let y_diff = y - y0;
+    let (x0, y0) = (x0 as i32, y0 as i32);
+
+    let mut neighbors = Vec::with_capacity(4);
+    for &(nx, ny) in &[(x0, y0), (x0 + 1, y0), (x0, y0 + 1), (x0 + 1, y0 + 1)] {
+        if nx >= 0 && nx < src.width && ny >= 0 && ny < src.height {
+            neighbors.push(src.get_pixel(nx, ny)?);
+        } else {
+            neighbors.push(bg.clone());
+        }
+    }
 
-// Rotate a point clockwise to a given degree.
-fn _rotate(p: (i32, i32), deg: f32) -> (i32, i32) {
-    let radians: f32 = deg.to_radians();
-    let px: f32 = p.0 as f32;
-    let py: f32 = p.1 as f32;
-    let cos = radians.cos();
-    let sin = radians.sin();
-    let x = ((px * cos) - (py * sin)).round();
-    let y = ((px * sin) + (py * cos)).round();
-    (x as i32, y as i32)
+    let weights = [
+        (1.0 - x_diff) * (1.0 - y_diff),
+        x_diff * (1.0 - y_diff),
+        (1.0 - x_diff) * y_diff,
+        x_diff * y_diff,
+    ];
+    let mut channels = [0.0f32; 4];
+    for (pixel, weight) in neighbors.iter().zip(weights.iter()) {
+        channels[0] += pixel.r as f32 * weight;
+        channels[1] += pixel.g as f32 * weight;
+        channels[2] += pixel.b as f32 * weight;
+        channels[3] += pixel.a as f32 * weight;
+    }
+
+    Ok(Color::rgba(
+        channels[0].round() as u8,
+        channels[1].round() as u8,
+        channels[2].round() as u8,
+        channels[3].round() as u8,
+    ))
+}
+
+// Swap width and height and move each pixel at (x, y) to the position given by dest.
+fn rotate_orthogonal<F>(src: &mut Image, dest: F)
+where
+    F: Fn(usize, usize) -> (usize, usize),
+{
+    let w = src.width as usize;
+    let h = src.height as usize;
+    let mut bytes = vec![0; src.bytes.len()];
+
+    for y in 0..h {
+        for x in 0..w {
+            let (dest_x, dest_y) = dest(x, y);
+            let from = (y * w + x) * 4;
+            let to = (dest_y * h + dest_x) * 4;
+            bytes[to..to + 4].copy_from_slice(&src.bytes[from..from + 4]);
+        }
+    }
+
+    src.width = h as i32;
+    src.height = w as i32;
+    src.bytes = bytes;
 }
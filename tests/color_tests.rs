@@ -41,3 +41,195 @@ fn hex_test() {
     let color = Color::hex("#FFF");
     assert!(color.is_err());
 }
+
+#[test]
+fn hsv_round_trip_test() {
+    for r in (0..256).step_by(15) {
+        for g in (0..256).step_by(15) {
+            for b in (0..256).step_by(15) {
+                let color = Color::rgb(r as u8, g as u8, b as u8);
+                let (h, s, v) = color.hsv();
+                assert!((0.0..360.0).contains(&h));
+                let color2 = Color::from_hsv(h, s, v);
+                assert!((color.r as i32 - color2.r as i32).abs() <= 1);
+                assert!((color.g as i32 - color2.g as i32).abs() <= 1);
+                assert!((color.b as i32 - color2.b as i32).abs() <= 1);
+                assert_eq!(255, color2.a);
+            }
+        }
+    }
+}
+
+#[test]
+fn hsv_gray_test() {
+    let (h, s, v) = Color::rgb(128, 128, 128).hsv();
+    assert_eq!(0.0, h);
+    assert_eq!(0.0, s);
+    assert!((v - 128.0 / 255.0).abs() < 0.001);
+}
+
+#[test]
+fn hsl_test() {
+    let (h, s, l) = Color::rgba(255, 0, 0, 255).hsl();
+    assert!(h.abs() < 0.001);
+    assert!((s - 1.0).abs() < 0.001);
+    assert!((l - 0.5).abs() < 0.001);
+
+    // Grays have no saturation
+    let (h, s, l) = Color::rgb(64, 64, 64).hsl();
+    assert_eq!(0.0, h);
+    assert_eq!(0.0, s);
+    assert!((l - 64.0 / 255.0).abs() < 0.001);
+}
+
+#[test]
+fn hsl_round_trip_test() {
+    for r in (0..256).step_by(15) {
+        for g in (0..256).step_by(15) {
+            for b in (0..256).step_by(15) {
+                let color = Color::rgb(r as u8, g as u8, b as u8);
+                let (h, s, l) = color.hsl();
+                let color2 = Color::from_hsl(h, s, l);
+                assert!((color.r as i32 - color2.r as i32).abs() <= 1);
+                assert!((color.g as i32 - color2.g as i32).abs() <= 1);
+                assert!((color.b as i32 - color2.b as i32).abs() <= 1);
+            }
+        }
+    }
+}
+
+#[test]
+fn to_hex_test() {
+    let color = Color::hex("#1a2b3c").unwrap();
+    assert_eq!("#1a2b3c", color.to_hex());
+
+    // Zero padded
+    assert_eq!("#000102", Color::rgb(0, 1, 2).to_hex());
+
+    // Alpha is only written when not opaque
+    assert_eq!("#ffffff", Color::rgba(255, 255, 255, 255).to_hex());
+    assert_eq!("#ffffff0f", Color::rgba(255, 255, 255, 15).to_hex());
+}
+
+#[test]
+fn lerp_test() {
+    let red = Color::red();
+    let blue = Color::blue();
+
+    let color = Color::lerp(&red, &blue, 0.5);
+    assert!(color.r == 127 || color.r == 128);
+    assert_eq!(0, color.g);
+    assert!(color.b == 127 || color.b == 128);
+    assert_eq!(255, color.a);
+
+    // End points and clamping
+    let color = Color::lerp(&red, &blue, -1.0);
+    assert_eq!((255, 0, 0, 255), (color.r, color.g, color.b, color.a));
+    let color = Color::lerp(&red, &blue, 2.0);
+    assert_eq!((0, 0, 255, 255), (color.r, color.g, color.b, color.a));
+}
+
+#[test]
+fn luminance_test() {
+    assert_eq!(150, Color::green().luminance());
+    assert_eq!(76, Color::red().luminance());
+    assert_eq!(29, Color::blue().luminance());
+    assert_eq!(0, Color::black().luminance());
+
+    // Alpha is ignored
+    assert_eq!(255, Color::rgba(255, 255, 255, 0).luminance());
+}
+
+#[test]
+fn distance_test() {
+    let black = Color::black();
+    let white = Color::white();
+    let max = black.distance(&white);
+    assert!((max - 441.67).abs() < 0.01);
+    assert_eq!(0.0, white.distance(&white));
+    assert!(Color::red().distance(&Color::blue()) < max);
+
+    // Alpha only counts in distance_rgba
+    let clear = Color::rgba(255, 255, 255, 0);
+    assert_eq!(0.0, white.distance(&clear));
+    assert_eq!(255.0, white.distance_rgba(&clear));
+    assert_eq!(510.0, Color::rgba(0, 0, 0, 0).distance_rgba(&white).round());
+}
+
+#[test]
+fn named_colors_test() {
+    let rgba = |c: Color| (c.r, c.g, c.b, c.a);
+    assert_eq!((255, 255, 255, 255), rgba(Color::white()));
+    assert_eq!((0, 0, 0, 255), rgba(Color::black()));
+    assert_eq!((0, 255, 0, 255), rgba(Color::green()));
+    assert_eq!((0, 0, 255, 255), rgba(Color::blue()));
+    assert_eq!((255, 255, 0, 255), rgba(Color::yellow()));
+    assert_eq!((0, 255, 255, 255), rgba(Color::cyan()));
+    assert_eq!((255, 0, 255, 255), rgba(Color::magenta()));
+    assert_eq!((128, 128, 128, 255), rgba(Color::gray()));
+    assert_eq!((0, 0, 0, 0), rgba(Color::transparent()));
+}
+
+#[test]
+fn cmyk_test() {
+    let (c, m, y, k) = Color::red().to_cmyk();
+    assert!(c.abs() < 0.001 && (m - 1.0).abs() < 0.001);
+    assert!((y - 1.0).abs() < 0.001 && k.abs() < 0.001);
+    assert_eq!((0.0, 0.0, 0.0, 1.0), Color::black().to_cmyk());
+
+    for &(r, g, b) in &[(0, 0, 0), (255, 255, 255), (12, 200, 99), (250, 3, 128), (1, 2, 3)] {
+        let (c, m, y, k) = Color::rgb(r, g, b).to_cmyk();
+        let color = Color::from_cmyk(c, m, y, k);
+        assert!(color.r.abs_diff(r) <= 1);
+        assert!(color.g.abs_diff(g) <= 1);
+        assert!(color.b.abs_diff(b) <= 1);
+        assert_eq!(255, color.a);
+    }
+}
+
+#[test]
+fn lab_test() {
+    let (l, a, b) = Color::white().to_lab();
+    assert!((l - 100.0).abs() < 0.1 && a.abs() < 0.1 && b.abs() < 0.1);
+    let (l, a, b) = Color::black().to_lab();
+    assert!(l.abs() < 0.1 && a.abs() < 0.1 && b.abs() < 0.1);
+
+    // Reference values for pure red
+    let (l, a, b) = Color::red().to_lab();
+    assert!((l - 53.24).abs() < 0.1);
+    assert!((a - 80.09).abs() < 0.1);
+    assert!((b - 67.20).abs() < 0.1);
+
+    for &(r, g, b) in &[(255, 0, 0), (12, 200, 99), (250, 3, 128), (1, 2, 3), (128, 128, 128)] {
+        let (l, a, b2) = Color::rgb(r, g, b).to_lab();
+        let color = Color::from_lab(l, a, b2);
+        assert!(color.r.abs_diff(r) <= 1);
+        assert!(color.g.abs_diff(g) <= 1);
+        assert!(color.b.abs_diff(b) <= 1);
+    }
+}
+
+#[test]
+fn delta_e_test() {
+    let orange = Color::rgb(255, 128, 0);
+    assert_eq!(0.0, orange.delta_e(&orange));
+    assert!(orange.delta_e(&Color::rgb(252, 129, 3)) < 3.0);
+    assert!(Color::black().delta_e(&Color::white()) > 99.0);
+    assert!(Color::green().delta_e(&Color::magenta()) > 100.0);
+}
+
+#[test]
+fn premultiply_test() {
+    let color = Color::rgba(255, 255, 255, 128).premultiply();
+    assert_eq!((128, 128, 128, 128), (color.r, color.g, color.b, color.a));
+    let color = color.unpremultiply();
+    assert_eq!((255, 255, 255, 128), (color.r, color.g, color.b, color.a));
+
+    // Opaque colors are unchanged and fully transparent ones have no color left
+    let color = Color::rgb(12, 34, 56).premultiply().unpremultiply();
+    assert_eq!((12, 34, 56, 255), (color.r, color.g, color.b, color.a));
+    let color = Color::rgba(200, 100, 50, 0).premultiply();
+    assert_eq!((0, 0, 0, 0), (color.r, color.g, color.b, color.a));
+    let color = Color::rgba(200, 100, 50, 0).unpremultiply();
+    assert_eq!((0, 0, 0, 0), (color.r, color.g, color.b, color.a));
+}
@@ -0,0 +1,977 @@
+extern crate raster;
+
+use raster::error::RasterError;
+use raster::{
+    editor, filter, BlendMode, Color, GradientMode, Image, InterpolationMode, PositionMode,
+    ResizeMode,
+};
+
+// Horizontal white-to-black gradient, one column per gray level.
+fn gradient() -> Image {
+    let mut image = Image::blank(256, 4);
+    for y in 0..image.height {
+        for x in 0..image.width {
+            let v = 255 - x as u8;
+            image.set_pixel(x, y, &Color::rgb(v, v, v)).unwrap();
+        }
+    }
+    image
+}
+
+#[test]
+fn blend_darken_test() {
+    let mut base = Image::blank(256, 4);
+    editor::fill(&mut base, Color::rgb(128, 128, 128)).unwrap();
+    let top = gradient();
+
+    let image = editor::blend(
+        &base,
+        &top,
+        BlendMode::Darken,
+        1.0,
+        PositionMode::TopLeft,
+        0,
+        0,
+    )
+    .unwrap();
+    raster::save(&image, "tests/out/test_blend_darken_gradient.png").unwrap();
+
+    for x in 0..image.width {
+        let pixel = image.get_pixel(x, 0).unwrap();
+        let expected = std::cmp::min(128, 255 - x) as u8;
+        assert_eq!(expected, pixel.r);
+        assert_eq!(expected, pixel.g);
+        assert_eq!(expected, pixel.b);
+    }
+}
+
+#[test]
+fn blend_lighten_test() {
+    let mut base = Image::blank(256, 4);
+    editor::fill(&mut base, Color::rgb(128, 128, 128)).unwrap();
+    let top = gradient();
+
+    let image = editor::blend(
+        &base,
+        &top,
+        BlendMode::Lighten,
+        1.0,
+        PositionMode::TopLeft,
+        0,
+        0,
+    )
+    .unwrap();
+    raster::save(&image, "tests/out/test_blend_lighten_gradient.png").unwrap();
+
+    for x in 0..image.width {
+        let pixel = image.get_pixel(x, 0).unwrap();
+        let expected = std::cmp::max(128, 255 - x) as u8;
+        assert_eq!(expected, pixel.r);
+    }
+
+    // A half transparent top pixel only lightens halfway.
+    let mut top = Image::blank(1, 1);
+    top.set_pixel(0, 0, &Color::rgba(255, 255, 255, 128))
+        .unwrap();
+    let image = editor::blend(
+        &base,
+        &top,
+        BlendMode::Lighten,
+        1.0,
+        PositionMode::TopLeft,
+        0,
+        0,
+    )
+    .unwrap();
+    let pixel = image.get_pixel(0, 0).unwrap();
+    assert_eq!(191, pixel.r);
+}
+
+#[test]
+fn blend_color_dodge_test() {
+    let mut base = Image::blank(2, 1);
+    base.set_pixel(0, 0, &Color::rgb(0, 0, 0)).unwrap();
+    base.set_pixel(1, 0, &Color::rgb(100, 100, 100)).unwrap();
+    let mut top = Image::blank(2, 1);
+    editor::fill(&mut top, Color::white()).unwrap();
+
+    // A white top would divide by zero, it should give pure white instead.
+    let image = editor::blend(
+        &base,
+        &top,
+        BlendMode::ColorDodge,
+        1.0,
+        PositionMode::TopLeft,
+        0,
+        0,
+    )
+    .unwrap();
+    for x in 0..2 {
+        let pixel = image.get_pixel(x, 0).unwrap();
+        assert_eq!((255, 255, 255), (pixel.r, pixel.g, pixel.b));
+    }
+}
+
+#[test]
+fn blend_color_burn_test() {
+    let mut base = Image::blank(2, 1);
+    base.set_pixel(0, 0, &Color::rgb(255, 255, 255)).unwrap();
+    base.set_pixel(1, 0, &Color::rgb(100, 100, 100)).unwrap();
+    let top = Image::blank(2, 1);
+
+    // A black top would divide by zero, it should give pure black instead.
+    let image = editor::blend(
+        &base,
+        &top,
+        BlendMode::ColorBurn,
+        1.0,
+        PositionMode::TopLeft,
+        0,
+        0,
+    )
+    .unwrap();
+    for x in 0..2 {
+        let pixel = image.get_pixel(x, 0).unwrap();
+        assert_eq!((0, 0, 0), (pixel.r, pixel.g, pixel.b));
+    }
+}
+
+#[test]
+fn blend_soft_light_test() {
+    let base = raster::open("tests/in/sample.jpg").unwrap();
+    let mut top = Image::blank(base.width, base.height);
+    editor::fill(&mut top, Color::rgb(128, 128, 128)).unwrap();
+
+    // Soft light with 50% gray should leave the base essentially unchanged.
+    let image = editor::blend(
+        &base,
+        &top,
+        BlendMode::SoftLight,
+        1.0,
+        PositionMode::TopLeft,
+        0,
+        0,
+    )
+    .unwrap();
+    raster::save(&image, "tests/out/test_blend_soft_light_gray.jpg").unwrap();
+    for y in 0..base.height {
+        for x in 0..base.width {
+            let p1 = base.get_pixel(x, y).unwrap();
+            let p2 = image.get_pixel(x, y).unwrap();
+            assert!((p1.r as i32 - p2.r as i32).abs() <= 1);
+            assert!((p1.g as i32 - p2.g as i32).abs() <= 1);
+            assert!((p1.b as i32 - p2.b as i32).abs() <= 1);
+        }
+    }
+}
+
+#[test]
+fn blend_hard_light_test() {
+    let mut base = Image::blank(256, 1);
+    for x in 0..base.width {
+        base.set_pixel(x, 0, &Color::rgb(x as u8, x as u8, x as u8))
+            .unwrap();
+    }
+    let mut top = Image::blank(256, 1);
+    editor::fill(&mut top, Color::rgb(128, 128, 128)).unwrap();
+
+    // Hard light with 50% gray is overlay with the layers swapped, which keeps the base as is.
+    let image = editor::blend(
+        &base,
+        &top,
+        BlendMode::HardLight,
+        1.0,
+        PositionMode::TopLeft,
+        0,
+        0,
+    )
+    .unwrap();
+    for x in 0..base.width {
+        let pixel = image.get_pixel(x, 0).unwrap();
+        assert!((pixel.r as i32 - x).abs() <= 1);
+    }
+}
+
+#[test]
+fn blend_exclusion_test() {
+    let image = raster::open("tests/in/sample.jpg").unwrap();
+    let excluded = editor::blend(
+        &image,
+        &image,
+        BlendMode::Exclusion,
+        1.0,
+        PositionMode::TopLeft,
+        0,
+        0,
+    )
+    .unwrap();
+    raster::save(&excluded, "tests/out/test_blend_exclusion_self.jpg").unwrap();
+
+    // Black and white both map to black, mid-gray peaks at the center.
+    let top = gradient();
+    let image = editor::blend(
+        &top,
+        &top,
+        BlendMode::Exclusion,
+        1.0,
+        PositionMode::TopLeft,
+        0,
+        0,
+    )
+    .unwrap();
+    for x in 0..image.width {
+        let v = (255 - x) as f32;
+        let expected = (v + v - 2.0 * v * v / 255.0) as u8;
+        assert_eq!(expected, image.get_pixel(x, 0).unwrap().r);
+    }
+    assert_eq!(0, image.get_pixel(0, 0).unwrap().r);
+    assert_eq!(0, image.get_pixel(255, 0).unwrap().r);
+    assert_eq!(127, image.get_pixel(127, 0).unwrap().r);
+}
+
+#[test]
+fn blend_addition_test() {
+    let mut base = Image::blank(10, 10);
+    editor::fill(&mut base, Color::rgb(40, 40, 40)).unwrap();
+    let mut top = Image::blank(10, 10); // Black
+    top.set_pixel(5, 5, &Color::rgb(240, 100, 0)).unwrap();
+
+    let image = editor::blend(
+        &base,
+        &top,
+        BlendMode::Addition,
+        1.0,
+        PositionMode::TopLeft,
+        0,
+        0,
+    )
+    .unwrap();
+    for y in 0..10 {
+        for x in 0..10 {
+            let pixel = image.get_pixel(x, y).unwrap();
+            if x == 5 && y == 5 {
+                assert_eq!((255, 140, 40), (pixel.r, pixel.g, pixel.b));
+            } else {
+                assert_eq!((40, 40, 40), (pixel.r, pixel.g, pixel.b));
+            }
+        }
+    }
+}
+
+#[test]
+fn blend_subtract_test() {
+    let mut base = Image::blank(1, 1);
+    base.set_pixel(0, 0, &Color::rgb(200, 100, 0)).unwrap();
+    let mut top = Image::blank(1, 1);
+    top.set_pixel(0, 0, &Color::rgb(50, 150, 10)).unwrap();
+
+    let image = editor::blend(
+        &base,
+        &top,
+        BlendMode::Subtract,
+        1.0,
+        PositionMode::TopLeft,
+        0,
+        0,
+    )
+    .unwrap();
+    let pixel = image.get_pixel(0, 0).unwrap();
+    assert_eq!((150, 0, 0), (pixel.r, pixel.g, pixel.b));
+}
+
+#[test]
+fn blend_mode_from_str_test() {
+    let modes = [
+        BlendMode::Normal,
+        BlendMode::Addition,
+        BlendMode::ColorBurn,
+        BlendMode::ColorDodge,
+        BlendMode::Darken,
+        BlendMode::Difference,
+        BlendMode::Exclusion,
+        BlendMode::HardLight,
+        BlendMode::Lighten,
+        BlendMode::Multiply,
+        BlendMode::Overlay,
+        BlendMode::Screen,
+        BlendMode::SoftLight,
+        BlendMode::Subtract,
+    ];
+    for mode in modes.iter() {
+        let parsed: BlendMode = mode.to_string().parse().unwrap();
+        assert_eq!(*mode, parsed);
+    }
+
+    match "overlay ".parse::<BlendMode>() {
+        Err(raster::error::RasterError::InvalidBlendMode(name)) => assert_eq!("overlay ", name),
+        _ => panic!("expected InvalidBlendMode"),
+    }
+}
+
+#[test]
+fn resize_mode_from_str_test() {
+    let modes = [
+        ResizeMode::Exact,
+        ResizeMode::ExactWidth,
+        ResizeMode::ExactHeight,
+        ResizeMode::Fit,
+        ResizeMode::Fill,
+    ];
+    for mode in modes.iter() {
+        let parsed: ResizeMode = mode.to_string().parse().unwrap();
+        assert_eq!(*mode, parsed);
+    }
+
+    match "stretch".parse::<ResizeMode>() {
+        Err(raster::error::RasterError::InvalidResizeMode(name)) => assert_eq!("stretch", name),
+        _ => panic!("expected InvalidResizeMode"),
+    }
+}
+
+#[test]
+fn crop_large_test() {
+    let mut image = Image::blank(4000, 3000);
+    for (i, byte) in image.bytes.iter_mut().enumerate() {
+        *byte = (i % 251) as u8;
+    }
+    let src = image.clone();
+
+    editor::crop(&mut image, 1500, 1000, PositionMode::Center, 30, -20).unwrap();
+
+    // Compare against a pixel by pixel copy of the same region.
+    assert_eq!(1500, image.width);
+    assert_eq!(1000, image.height);
+    assert_eq!(1500 * 1000 * 4, image.bytes.len());
+    let (offset_x, offset_y) = (2000 - 750 + 30, 1500 - 500 - 20);
+    for y in 0..image.height {
+        for x in 0..image.width {
+            let expected = src.get_pixel(offset_x + x, offset_y + y).unwrap();
+            let actual = image.get_pixel(x, y).unwrap();
+            assert_eq!(
+                (expected.r, expected.g, expected.b, expected.a),
+                (actual.r, actual.g, actual.b, actual.a)
+            );
+        }
+    }
+}
+
+#[test]
+fn crop_to_test() {
+    let image = raster::open("tests/in/crop-test.jpg").unwrap();
+    let (width, height) = (image.width, image.height);
+
+    let cropped = editor::crop_to(&image, 167, 93, PositionMode::BottomRight, 0, 0).unwrap();
+
+    // Source is untouched
+    assert_eq!(width, image.width);
+    assert_eq!(height, image.height);
+
+    // Same result as the in place crop
+    let mut expected = image.clone();
+    editor::crop(&mut expected, 167, 93, PositionMode::BottomRight, 0, 0).unwrap();
+    assert_eq!(expected.width, cropped.width);
+    assert_eq!(expected.height, cropped.height);
+    assert_eq!(expected.bytes, cropped.bytes);
+}
+
+#[test]
+fn gradient_test() {
+    let mut image = Image::blank(256, 10);
+    editor::gradient(
+        &mut image,
+        Color::black(),
+        Color::white(),
+        GradientMode::Horizontal,
+    )
+    .unwrap();
+    for x in 0..image.width {
+        assert_eq!(x as u8, image.get_pixel(x, 5).unwrap().r);
+    }
+
+    let mut image = Image::blank(10, 11);
+    editor::gradient(
+        &mut image,
+        Color::red(),
+        Color::blue(),
+        GradientMode::Vertical,
+    )
+    .unwrap();
+    let top = image.get_pixel(3, 0).unwrap();
+    let bottom = image.get_pixel(3, 10).unwrap();
+    assert_eq!((255, 0, 0), (top.r, top.g, top.b));
+    assert_eq!((0, 0, 255), (bottom.r, bottom.g, bottom.b));
+
+    let mut image = Image::blank(11, 11);
+    editor::gradient(
+        &mut image,
+        Color::black(),
+        Color::white(),
+        GradientMode::Diagonal,
+    )
+    .unwrap();
+    assert_eq!(0, image.get_pixel(0, 0).unwrap().r);
+    assert_eq!(128, image.get_pixel(10, 0).unwrap().r);
+    assert_eq!(128, image.get_pixel(0, 10).unwrap().r);
+    assert_eq!(255, image.get_pixel(10, 10).unwrap().r);
+}
+
+#[test]
+fn flood_fill_test() {
+    // White canvas with a black 1px square outline from (2, 2) to (7, 7)
+    let mut image = Image::blank(10, 10);
+    editor::fill(&mut image, Color::white()).unwrap();
+    for i in 2..8 {
+        for &(x, y) in &[(i, 2), (i, 7), (2, i), (7, i)] {
+            image.set_pixel(x, y, &Color::black()).unwrap();
+        }
+    }
+
+    editor::flood_fill(&mut image, 4, 4, Color::red(), 0).unwrap();
+    for y in 0..10 {
+        for x in 0..10 {
+            let p = image.get_pixel(x, y).unwrap();
+            let expected = if (3..7).contains(&x) && (3..7).contains(&y) {
+                (255, 0, 0)
+            } else if (2..8).contains(&x) && (2..8).contains(&y) {
+                (0, 0, 0)
+            } else {
+                (255, 255, 255)
+            };
+            assert_eq!(expected, (p.r, p.g, p.b));
+        }
+    }
+
+    // Tolerance lets the fill through near colors
+    let mut image = Image::blank(4, 1);
+    editor::fill(&mut image, Color::rgb(100, 100, 100)).unwrap();
+    image.set_pixel(2, 0, &Color::rgb(105, 100, 100)).unwrap();
+    let mut strict = image.clone();
+    editor::flood_fill(&mut strict, 0, 0, Color::red(), 0).unwrap();
+    assert_eq!(100, strict.get_pixel(3, 0).unwrap().r);
+    editor::flood_fill(&mut image, 0, 0, Color::red(), 5).unwrap();
+    assert_eq!(255, image.get_pixel(3, 0).unwrap().r);
+
+    assert!(editor::flood_fill(&mut image, 4, 0, Color::red(), 0).is_err());
+    assert!(editor::flood_fill(&mut image, -1, 0, Color::red(), 0).is_err());
+}
+
+#[test]
+fn replace_color_test() {
+    // Blue background with a red subject in the middle
+    let mut image = Image::blank(6, 6);
+    editor::fill(&mut image, Color::rgb(0, 0, 250)).unwrap();
+    image.set_pixel(1, 1, &Color::rgb(3, 0, 255)).unwrap();
+    for y in 2..4 {
+        for x in 2..4 {
+            image.set_pixel(x, y, &Color::red()).unwrap();
+        }
+    }
+
+    editor::replace_color(&mut image, Color::blue(), Color::rgba(0, 0, 0, 0), 5).unwrap();
+    for y in 0..6 {
+        for x in 0..6 {
+            let p = image.get_pixel(x, y).unwrap();
+            if (2..4).contains(&x) && (2..4).contains(&y) {
+                assert_eq!((255, 0, 0, 255), (p.r, p.g, p.b, p.a));
+            } else {
+                assert_eq!(0, p.a);
+            }
+        }
+    }
+}
+
+#[test]
+fn color_to_alpha_test() {
+    // Green screen with a red subject in the middle and a green-ish fringe pixel
+    let mut image = Image::blank(6, 6);
+    editor::fill(&mut image, Color::rgb(0, 250, 5)).unwrap();
+    image.set_pixel(0, 5, &Color::rgb(15, 240, 0)).unwrap();
+    for y in 2..4 {
+        for x in 2..4 {
+            image.set_pixel(x, y, &Color::red()).unwrap();
+        }
+    }
+
+    editor::color_to_alpha(&mut image, Color::green(), 10).unwrap();
+    assert_eq!(0, image.get_pixel(0, 0).unwrap().a);
+    assert_eq!(127, image.get_pixel(0, 5).unwrap().a);
+    for y in 2..4 {
+        for x in 2..4 {
+            let p = image.get_pixel(x, y).unwrap();
+            assert_eq!((255, 0, 0, 255), (p.r, p.g, p.b, p.a));
+        }
+    }
+}
+
+#[test]
+fn draw_rectangle_filled_test() {
+    let mut image = Image::blank(10, 10);
+    editor::fill(&mut image, Color::white()).unwrap();
+    editor::draw_rectangle(&mut image, 2, 3, 4, 5, Color::rgba(0, 0, 255, 128), true).unwrap();
+
+    for y in 0..10 {
+        for x in 0..10 {
+            let p = image.get_pixel(x, y).unwrap();
+            if (2..6).contains(&x) && (3..8).contains(&y) {
+                assert_eq!((126, 126, 255, 255), (p.r, p.g, p.b, p.a));
+            } else {
+                assert_eq!((255, 255, 255), (p.r, p.g, p.b));
+            }
+        }
+    }
+}
+
+#[test]
+fn draw_rectangle_clipped_test() {
+    let mut image = Image::blank(10, 10);
+    editor::fill(&mut image, Color::white()).unwrap();
+    editor::draw_rectangle(&mut image, -3, 6, 8, 8, Color::red(), false).unwrap();
+
+    for y in 0..10 {
+        for x in 0..10 {
+            let p = image.get_pixel(x, y).unwrap();
+            let outline = (x == 4 && y >= 6) || (y == 6 && x <= 4);
+            assert_eq!(outline, (p.r, p.g, p.b) == (255, 0, 0), "pixel {}, {}", x, y);
+        }
+    }
+}
+
+// Coordinates of every pixel that is not white.
+fn drawn_pixels(image: &Image) -> Vec<(i32, i32)> {
+    let mut drawn = Vec::new();
+    for y in 0..image.height {
+        for x in 0..image.width {
+            let p = image.get_pixel(x, y).unwrap();
+            if (p.r, p.g, p.b) != (255, 255, 255) {
+                drawn.push((x, y));
+            }
+        }
+    }
+    drawn
+}
+
+#[test]
+fn draw_line_test() {
+    let mut horizontal = Image::blank(6, 6);
+    editor::fill(&mut horizontal, Color::white()).unwrap();
+    editor::draw_line(&mut horizontal, 1, 2, 4, 2, Color::black()).unwrap();
+    assert_eq!(vec![(1, 2), (2, 2), (3, 2), (4, 2)], drawn_pixels(&horizontal));
+
+    // Clipped at the bottom of the canvas
+    let mut vertical = Image::blank(6, 6);
+    editor::fill(&mut vertical, Color::white()).unwrap();
+    editor::draw_line(&mut vertical, 3, 3, 3, 20, Color::black()).unwrap();
+    assert_eq!(vec![(3, 3), (3, 4), (3, 5)], drawn_pixels(&vertical));
+
+    // Drawn right to left and clipped on both ends
+    let mut diagonal = Image::blank(6, 6);
+    editor::fill(&mut diagonal, Color::white()).unwrap();
+    editor::draw_line(&mut diagonal, 7, 7, -2, -2, Color::black()).unwrap();
+    let expected: Vec<(i32, i32)> = (0..6).map(|i| (i, i)).collect();
+    assert_eq!(expected, drawn_pixels(&diagonal));
+
+    // Far away end points are clipped before the line is walked
+    let mut far = Image::blank(6, 6);
+    editor::fill(&mut far, Color::white()).unwrap();
+    editor::draw_line(&mut far, i32::MIN, i32::MIN, i32::MAX, i32::MAX, Color::black()).unwrap();
+    assert_eq!(expected, drawn_pixels(&far));
+    let mut far = Image::blank(6, 6);
+    editor::fill(&mut far, Color::white()).unwrap();
+    editor::draw_line(&mut far, -2_000_000_000, 2, 2_000_000_000, 2, Color::black()).unwrap();
+    let expected: Vec<(i32, i32)> = (0..6).map(|x| (x, 2)).collect();
+    assert_eq!(expected, drawn_pixels(&far));
+
+    // Lines that miss the canvas draw nothing
+    let mut missed = Image::blank(6, 6);
+    editor::fill(&mut missed, Color::white()).unwrap();
+    editor::draw_line(&mut missed, -5, 8, 8, 20, Color::black()).unwrap();
+    assert!(drawn_pixels(&missed).is_empty());
+}
+
+#[test]
+fn draw_circle_test() {
+    let mut image = Image::blank(20, 20);
+    editor::fill(&mut image, Color::white()).unwrap();
+    editor::draw_circle(&mut image, 10, 10, 8, Color::rgba(0, 0, 0, 128), true).unwrap();
+
+    // Blended exactly once, even where spans of the octants meet
+    for &(x, y) in &[(10, 10), (10, 2), (18, 10), (5, 5), (15, 15)] {
+        let p = image.get_pixel(x, y).unwrap();
+        assert_eq!((126, 126, 126), (p.r, p.g, p.b), "pixel {}, {}", x, y);
+    }
+    for &(x, y) in &[(0, 0), (4, 4), (16, 16), (10, 1)] {
+        let p = image.get_pixel(x, y).unwrap();
+        assert_eq!((255, 255, 255), (p.r, p.g, p.b), "pixel {}, {}", x, y);
+    }
+    for y in 0..20 {
+        for x in 0..20 {
+            let r = image.get_pixel(x, y).unwrap().r;
+            assert!(r == 126 || r == 255, "pixel {}, {}", x, y);
+        }
+    }
+
+    // Clipped by the canvas
+    let mut outline = Image::blank(20, 20);
+    editor::fill(&mut outline, Color::white()).unwrap();
+    editor::draw_circle(&mut outline, 0, 0, 5, Color::black(), false).unwrap();
+    assert_eq!(Color::black().r, outline.get_pixel(5, 0).unwrap().r);
+    assert_eq!(Color::black().r, outline.get_pixel(0, 5).unwrap().r);
+    assert_eq!(255, outline.get_pixel(1, 1).unwrap().r);
+
+    // Huge circles only cost the rows inside the canvas. The top of this one touches row 0.
+    let (cy, radius) = (1_000_000_000, 1_000_000_000);
+    let mut huge = Image::blank(20, 20);
+    editor::fill(&mut huge, Color::white()).unwrap();
+    editor::draw_circle(&mut huge, 10, cy, radius, Color::black(), false).unwrap();
+    let expected: Vec<(i32, i32)> = (0..20).map(|x| (x, 0)).collect();
+    assert_eq!(expected, drawn_pixels(&huge));
+    editor::draw_circle(&mut huge, 10, cy, radius, Color::black(), true).unwrap();
+    assert_eq!(400, drawn_pixels(&huge).len());
+
+    // Circles whose bounding box misses the canvas draw nothing
+    let mut missed = Image::blank(20, 20);
+    editor::fill(&mut missed, Color::white()).unwrap();
+    editor::draw_circle(&mut missed, -i32::MAX, 10, i32::MAX - 1, Color::black(), true).unwrap();
+    editor::draw_circle(&mut missed, 10, 30, 5, Color::black(), true).unwrap();
+    assert!(drawn_pixels(&missed).is_empty());
+}
+
+#[test]
+fn draw_polygon_test() {
+    let mut image = Image::blank(12, 12);
+    editor::fill(&mut image, Color::white()).unwrap();
+    let triangle = [(1, 1), (11, 1), (1, 11)];
+    editor::draw_polygon(&mut image, &triangle, Color::black(), true).unwrap();
+
+    assert_eq!(0, image.get_pixel(3, 3).unwrap().r);
+    assert_eq!(0, image.get_pixel(5, 4).unwrap().r);
+    // Just past the hypotenuse
+    assert_eq!(255, image.get_pixel(6, 6).unwrap().r);
+    assert_eq!(255, image.get_pixel(0, 5).unwrap().r);
+
+    // Concave: the notch of an arrow head stays empty
+    let mut concave = Image::blank(12, 12);
+    editor::fill(&mut concave, Color::white()).unwrap();
+    let arrow = [(0, 0), (11, 6), (0, 12), (5, 6)];
+    editor::draw_polygon(&mut concave, &arrow, Color::black(), true).unwrap();
+    assert_eq!(0, concave.get_pixel(7, 6).unwrap().r);
+    assert_eq!(255, concave.get_pixel(2, 6).unwrap().r);
+
+    assert!(editor::draw_polygon(&mut image, &[(0, 0), (5, 5)], Color::black(), false).is_err());
+}
+
+#[test]
+fn add_border_test() {
+    let original = gradient();
+    let mut image = original.clone();
+    editor::resize(&mut image, 100, 100, ResizeMode::Exact).unwrap();
+    let resized = image.clone();
+
+    editor::add_border(&mut image, 10, Color::red()).unwrap();
+    assert_eq!((120, 120), (image.width, image.height));
+    assert_eq!(120 * 120 * 4, image.bytes.len());
+    for y in 0..120 {
+        for x in 0..120 {
+            let p = image.get_pixel(x, y).unwrap();
+            let expected = if (10..110).contains(&x) && (10..110).contains(&y) {
+                resized.get_pixel(x - 10, y - 10).unwrap()
+            } else {
+                Color::red()
+            };
+            assert_eq!((expected.r, expected.g, expected.b, expected.a), (p.r, p.g, p.b, p.a));
+        }
+    }
+}
+
+#[test]
+fn pad_test() {
+    let original = gradient();
+    let mut image = original.clone();
+    editor::pad(&mut image, 20, 0, 0, 0, Color::white()).unwrap();
+
+    assert_eq!((original.width, original.height + 20), (image.width, image.height));
+    let offset = 20 * image.width as usize * 4;
+    assert_eq!(&original.bytes[..], &image.bytes[offset..]);
+    assert!(image.bytes[..offset].iter().all(|&b| b == 255));
+
+    assert!(editor::pad(&mut image, 0, -1, 0, 0, Color::white()).is_err());
+}
+
+#[test]
+fn round_corners_test() {
+    let mut image = Image::blank(40, 30);
+    editor::fill(&mut image, Color::red()).unwrap();
+    editor::round_corners(&mut image, 10).unwrap();
+
+    for &(x, y) in &[(0, 0), (39, 0), (0, 29), (39, 29), (1, 1)] {
+        assert_eq!(0, image.get_pixel(x, y).unwrap().a, "pixel {}, {}", x, y);
+    }
+    // Anti-aliased edge
+    let edge = image.get_pixel(2, 3).unwrap().a;
+    assert!(edge > 0 && edge < 255);
+
+    for &(x, y) in &[(20, 15), (10, 0), (0, 10), (7, 7)] {
+        assert_eq!(255, image.get_pixel(x, y).unwrap().a, "pixel {}, {}", x, y);
+    }
+}
+
+#[test]
+fn trim_test() {
+    // 10px near-white margin around a 30x20 red to blue gradient
+    let mut content = Image::blank(30, 20);
+    editor::gradient(&mut content, Color::red(), Color::blue(), GradientMode::Diagonal).unwrap();
+    let mut image = content.clone();
+    editor::add_border(&mut image, 10, Color::rgb(250, 252, 255)).unwrap();
+
+    editor::trim(&mut image, Color::white(), 5).unwrap();
+    assert_eq!((30, 20), (image.width, image.height));
+    assert_eq!(content.bytes, image.bytes);
+
+    // Nothing but background
+    let mut blank = Image::blank(8, 8);
+    editor::fill(&mut blank, Color::white()).unwrap();
+    editor::trim(&mut blank, Color::white(), 0).unwrap();
+    assert_eq!((1, 1), (blank.width, blank.height));
+}
+
+// The rows are blended in parallel with the rayon feature, serially without it. Both must
+// match the same expected images.
+#[test]
+fn blend_parallel_test() {
+    let mut base = Image::blank(300, 300);
+    editor::gradient(&mut base, Color::red(), Color::blue(), GradientMode::Diagonal).unwrap();
+    let mut top = Image::blank(300, 300);
+    editor::gradient(&mut top, Color::rgba(255, 255, 0, 64), Color::green(), GradientMode::Vertical)
+        .unwrap();
+
+    for name in &["normal", "multiply", "soft-light"] {
+        let mode = name.parse().unwrap();
+        let image = editor::blend(&base, &top, mode, 0.8, PositionMode::Center, 45, -30).unwrap();
+        let expected = raster::open(&format!("tests/in/blend-{}.png", name)).unwrap();
+        assert_eq!(expected.bytes, image.bytes);
+    }
+}
+
+#[test]
+fn resize_with_test() {
+    // 2x2 black and white checkerboard
+    let mut checkerboard = Image::blank(2, 2);
+    checkerboard.set_pixel(1, 0, &Color::white()).unwrap();
+    checkerboard.set_pixel(0, 1, &Color::white()).unwrap();
+    let grays = |image: &Image| {
+        image
+            .bytes
+            .chunks(4)
+            .filter(|p| p[0] != 0 && p[0] != 255)
+            .count()
+    };
+
+    let mut nearest = checkerboard.clone();
+    editor::resize_with(&mut nearest, 8, 8, ResizeMode::Exact, InterpolationMode::Nearest).unwrap();
+    assert_eq!((8, 8), (nearest.width, nearest.height));
+    assert_eq!(0, grays(&nearest));
+
+    let mut bilinear = checkerboard.clone();
+    let mode = InterpolationMode::Bilinear;
+    editor::resize_with(&mut bilinear, 8, 8, ResizeMode::Exact, mode).unwrap();
+    assert_eq!((8, 8), (bilinear.width, bilinear.height));
+    assert!(grays(&bilinear) > 0);
+}
+
+#[test]
+fn resize_scale_test() {
+    let mut image = Image::blank(200, 100);
+    editor::resize_scale(&mut image, 0.5).unwrap();
+    assert_eq!((100, 50), (image.width, image.height));
+
+    editor::resize_scale(&mut image, 2.0).unwrap();
+    assert_eq!((200, 100), (image.width, image.height));
+
+    editor::resize_scale(&mut image, 0.001).unwrap();
+    assert_eq!((1, 1), (image.width, image.height));
+
+    assert!(editor::resize_scale(&mut image, 0.0).is_err());
+    assert!(editor::resize_scale(&mut image, -1.0).is_err());
+}
+
+#[test]
+fn thumbnail_test() {
+    // Already fits, never upscaled
+    let mut small = gradient();
+    editor::resize(&mut small, 80, 20, ResizeMode::Exact).unwrap();
+    let original = small.clone();
+    editor::thumbnail(&mut small, 100, 100).unwrap();
+    assert_eq!(original.bytes, small.bytes);
+
+    // Wide image touches the width
+    let mut large = Image::blank(400, 100);
+    editor::thumbnail(&mut large, 100, 100).unwrap();
+    assert_eq!((100, 25), (large.width, large.height));
+
+    // Tall image touches the height
+    let mut tall = Image::blank(150, 600);
+    editor::thumbnail(&mut tall, 100, 100).unwrap();
+    assert_eq!((25, 100), (tall.width, tall.height));
+}
+
+#[test]
+fn diff_map_test() {
+    let image = raster::open("tests/in/sample.png").unwrap();
+    let mut inverted = image.clone();
+    filter::invert(&mut inverted).unwrap();
+
+    let diff = editor::diff_map(&image, &image).unwrap();
+    assert_eq!((image.width, image.height), (diff.width, diff.height));
+    assert!(diff.bytes.chunks(4).all(|p| p == [0, 0, 0, 255]));
+
+    let diff = editor::diff_map(&image, &inverted).unwrap();
+    assert!(diff.bytes.chunks(4).all(|p| p == [255, 0, 0, 255]));
+
+    assert!(editor::diff_map(&image, &Image::blank(2, 2)).is_err());
+}
+
+#[test]
+fn position_percent_test() {
+    let base = raster::open("tests/in/sample.jpg").unwrap();
+    let watermark = raster::open("tests/in/watermark.png").unwrap();
+    let blend = |position| {
+        editor::blend(&base, &watermark, BlendMode::Normal, 1.0, position, 0, 0).unwrap()
+    };
+
+    let percent = blend(PositionMode::Percent(100.0, 100.0));
+    assert_eq!(blend(PositionMode::BottomRight).bytes, percent.bytes);
+    // Clamped to 0 - 100
+    let clamped = blend(PositionMode::Percent(-50.0, 250.0));
+    assert_eq!(blend(PositionMode::BottomLeft).bytes, clamped.bytes);
+
+    let mut image = base.clone();
+    editor::crop(&mut image, 100, 50, PositionMode::Percent(25.0, 50.0), 0, 0).unwrap();
+    let x = ((base.width - 100) as f32 * 0.25).round() as i32;
+    let y = ((base.height - 50) as f32 * 0.5).round() as i32;
+    assert!(image.is_equal(&base.get_region(x, y, 100, 50).unwrap()));
+}
+
+#[test]
+fn position_absolute_test() {
+    let mut base = Image::blank(100, 100);
+    editor::fill(&mut base, Color::white()).unwrap();
+    let mut square = Image::blank(5, 5);
+    editor::fill(&mut square, Color::red()).unwrap();
+
+    let image = editor::blend(
+        &base,
+        &square,
+        BlendMode::Normal,
+        1.0,
+        PositionMode::Absolute(37, 52),
+        0,
+        0,
+    )
+    .unwrap();
+    let red = |x, y| image.get_pixel(x, y).unwrap().g == 0;
+    assert!(red(37, 52) && red(41, 56));
+    assert!(!red(36, 52) && !red(37, 51) && !red(42, 56) && !red(41, 57));
+
+    let mut cropped = image.clone();
+    editor::crop(&mut cropped, 5, 5, PositionMode::Absolute(37, 52), 0, 0).unwrap();
+    assert!(cropped.is_equal(&square));
+}
+
+#[test]
+fn blend_outside_canvas_test() {
+    let base = Image::blank(100, 80);
+    let top = Image::blank(20, 10);
+
+    let position = PositionMode::TopLeft;
+    let result = editor::blend(&base, &top, BlendMode::Normal, 1.0, position, 150, -30);
+    match result {
+        Err(RasterError::BlendingImageFallsOutsideCanvas {
+            offset,
+            canvas,
+            image,
+        }) => {
+            assert_eq!((150, -30), offset);
+            assert_eq!((100, 80), canvas);
+            assert_eq!((20, 10), image);
+        }
+        _ => panic!("expected BlendingImageFallsOutsideCanvas"),
+    }
+
+    let err = editor::blend(&base, &top, BlendMode::Normal, 1.0, PositionMode::Center, 0, 200)
+        .unwrap_err();
+    assert_eq!(
+        "blended 20x10 image at 40, 235 falls outside the 100x80 canvas",
+        err.to_string()
+    );
+
+    // Works as a std::error::Error
+    fn blend_boxed(base: &Image, top: &Image) -> Result<Image, Box<dyn std::error::Error>> {
+        Ok(editor::blend(base, top, BlendMode::Normal, 1.0, PositionMode::TopLeft, 150, 0)?)
+    }
+    let err = blend_boxed(&base, &top).unwrap_err();
+    assert_eq!(
+        "blended 20x10 image at 150, 0 falls outside the 100x80 canvas",
+        err.to_string()
+    );
+    assert!(err.source().is_none());
+}
+
+#[test]
+fn crop_square_test() {
+    // Columns 0 - 49 and 250 - 299 are red
+    let mut image = Image::blank_with_color(300, 200, Color::red());
+    for y in 0..200 {
+        for x in 50..250 {
+            image.set_pixel(x, y, &Color::blue()).unwrap();
+        }
+    }
+
+    editor::crop_square(&mut image).unwrap();
+    assert_eq!((200, 200), (image.width, image.height));
+    assert!(image.pixels().all(|(_, _, p)| p.b == 255 && p.r == 0));
+
+    let mut portrait = Image::blank(3, 8);
+    editor::crop_square(&mut portrait).unwrap();
+    assert_eq!((3, 3), (portrait.width, portrait.height));
+}
+
+#[test]
+fn fit_pad_test() {
+    let mut image = Image::blank_with_color(200, 100, Color::red());
+    editor::fit_pad(&mut image, 300, 300, Color::blue()).unwrap();
+    assert_eq!((300, 300), (image.width, image.height));
+
+    // 300x150 content in rows 75 - 224
+    let is_red = |y| image.get_pixel(150, y).unwrap().r == 255;
+    assert!(!is_red(74) && is_red(75));
+    assert!(is_red(224) && !is_red(225));
+    assert!(is_red(150));
+    assert_eq!(255, image.get_pixel(0, 0).unwrap().b);
+}
+
+#[test]
+fn watermark_test() {
+    let mut image = Image::blank_with_color(300, 200, Color::white());
+    let mark = Image::blank_with_color(40, 30, Color::red());
+    editor::watermark(&mut image, &mark, PositionMode::BottomRight, 1.0, 20).unwrap();
+
+    let is_red = |x, y| image.get_pixel(x, y).unwrap().g == 0;
+    assert!(is_red(240, 150) && is_red(279, 179));
+    assert!(!is_red(239, 150) && !is_red(240, 149));
+    assert!(!is_red(280, 179) && !is_red(279, 180));
+
+    let mut image = Image::blank_with_color(300, 200, Color::white());
+    editor::watermark(&mut image, &mark, PositionMode::TopLeft, 0.5, 10).unwrap();
+    let pixel = image.get_pixel(10, 10).unwrap();
+    assert!(pixel.g > 100 && pixel.g < 150 && pixel.r == 255);
+    assert_eq!(255, image.get_pixel(9, 10).unwrap().g);
+
+    for &margin in &[-1, 101] {
+        match editor::watermark(&mut image, &mark, PositionMode::Center, 1.0, margin) {
+            Err(RasterError::InvalidPadding(m)) => assert_eq!(margin, m),
+            _ => panic!("expected InvalidPadding"),
+        }
+    }
+    let err = editor::watermark(&mut image, &mark, PositionMode::Center, 1.0, 101).unwrap_err();
+    assert_eq!("invalid padding or margin 101", err.to_string());
+}
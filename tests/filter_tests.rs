@@ -1,6 +1,6 @@
 extern crate raster;
 
-use raster::{filter, Orientation};
+use raster::{editor, filter, Color, Image, Orientation};
 
 #[test]
 fn brightness_test() {
@@ -43,3 +43,561 @@ fn sobel_d2_test() {
     filter::sobel(&mut image, Orientation::DiagonalDown).unwrap();
     raster::save(&image, "tests/out/test_filter_sobel_d2.jpg").unwrap();
 }
+
+#[test]
+fn grayscale_test() {
+    let src = raster::open("tests/in/sample.jpg").unwrap();
+    let mut image = src.clone();
+    filter::grayscale(&mut image).unwrap();
+    raster::save(&image, "tests/out/test_filter_grayscale.jpg").unwrap();
+
+    for y in 0..src.height {
+        for x in 0..src.width {
+            let p1 = src.get_pixel(x, y).unwrap();
+            let p2 = image.get_pixel(x, y).unwrap();
+            let gray = p1.luminance();
+            assert_eq!((gray, gray, gray, p1.a), (p2.r, p2.g, p2.b, p2.a));
+        }
+    }
+}
+
+#[test]
+fn grayscale_alpha_test() {
+    let mut image = raster::open("tests/in/watermark.png").unwrap();
+    let alpha: Vec<u8> = image.bytes.iter().skip(3).step_by(4).cloned().collect();
+    filter::grayscale(&mut image).unwrap();
+    let alpha2: Vec<u8> = image.bytes.iter().skip(3).step_by(4).cloned().collect();
+    assert_eq!(alpha, alpha2);
+}
+
+#[test]
+fn sepia_test() {
+    let mut image = raster::open("tests/in/sample.jpg").unwrap();
+    filter::sepia(&mut image).unwrap();
+    raster::save(&image, "tests/out/test_sepia.jpg").unwrap();
+
+    // White turns into a warm off-white
+    let mut image = Image::blank(1, 1);
+    editor::fill(&mut image, Color::white()).unwrap();
+    filter::sepia(&mut image).unwrap();
+    let pixel = image.get_pixel(0, 0).unwrap();
+    assert_eq!((255, 255, 239, 255), (pixel.r, pixel.g, pixel.b, pixel.a));
+}
+
+#[test]
+fn invert_test() {
+    let src = raster::open("tests/in/sample.jpg").unwrap();
+    let mut image = src.clone();
+
+    filter::invert(&mut image).unwrap();
+    let p1 = src.get_pixel(10, 10).unwrap();
+    let p2 = image.get_pixel(10, 10).unwrap();
+    assert_eq!((255 - p1.r, 255 - p1.g, 255 - p1.b, p1.a), (p2.r, p2.g, p2.b, p2.a));
+
+    // Inverting twice gives back the original
+    filter::invert(&mut image).unwrap();
+    assert_eq!(src.bytes, image.bytes);
+}
+
+#[test]
+fn brightness_factor_test() {
+    let src = raster::open("tests/in/watermark.png").unwrap();
+
+    // 1.0 is identity
+    let mut image = src.clone();
+    filter::brightness(&mut image, 1.0).unwrap();
+    assert_eq!(src.bytes, image.bytes);
+
+    // 0.0 is black, alpha is preserved
+    let mut image = src.clone();
+    filter::brightness(&mut image, 0.0).unwrap();
+    for (p1, p2) in src.bytes.chunks(4).zip(image.bytes.chunks(4)) {
+        assert_eq!(&[0, 0, 0, p1[3]], p2);
+    }
+
+    // Large factors clamp instead of overflowing
+    let mut image = Image::blank(1, 1);
+    image.set_pixel(0, 0, &Color::rgba(10, 100, 200, 128)).unwrap();
+    filter::brightness(&mut image, 10.0).unwrap();
+    let pixel = image.get_pixel(0, 0).unwrap();
+    assert_eq!((100, 255, 255, 128), (pixel.r, pixel.g, pixel.b, pixel.a));
+}
+
+#[test]
+fn contrast_test() {
+    let src = raster::open("tests/in/sample.jpg").unwrap();
+
+    // 1.0 is identity
+    let mut image = src.clone();
+    filter::contrast(&mut image, 1.0).unwrap();
+    assert_eq!(src.bytes, image.bytes);
+
+    // 0.0 flattens everything to mid-gray
+    let mut image = src.clone();
+    filter::contrast(&mut image, 0.0).unwrap();
+    for pixel in image.bytes.chunks(4) {
+        assert_eq!(&[128, 128, 128, 255], pixel);
+    }
+
+    // High contrast clamps at the extremes instead of wrapping around
+    let mut image = Image::blank(3, 1);
+    image.set_pixel(0, 0, &Color::rgb(0, 100, 120)).unwrap();
+    image.set_pixel(1, 0, &Color::rgb(255, 156, 136)).unwrap();
+    image.set_pixel(2, 0, &Color::rgb(128, 128, 128)).unwrap();
+    filter::contrast(&mut image, 10.0).unwrap();
+    let p = image.get_pixel(0, 0).unwrap();
+    assert_eq!((0, 0, 48), (p.r, p.g, p.b));
+    let p = image.get_pixel(1, 0).unwrap();
+    assert_eq!((255, 255, 208), (p.r, p.g, p.b));
+    let p = image.get_pixel(2, 0).unwrap();
+    assert_eq!((128, 128, 128), (p.r, p.g, p.b));
+}
+
+#[test]
+fn gamma_test() {
+    let src = raster::open("tests/in/gamma_saturn.jpg").unwrap();
+
+    // 1.0 is identity
+    let mut image = src.clone();
+    filter::gamma(&mut image, 1.0).unwrap();
+    assert_eq!(src.bytes, image.bytes);
+
+    // Matches a direct computation and brightens midtones
+    let mut image = src.clone();
+    filter::gamma(&mut image, 2.2).unwrap();
+    raster::save(&image, "tests/out/test_filter_gamma_saturn.jpg").unwrap();
+    for (p1, p2) in src.bytes.chunks(4).zip(image.bytes.chunks(4)) {
+        for c in 0..3 {
+            let expected = (p1[c] as f32 / 255.0).powf(1.0 / 2.2) * 255.0;
+            assert!((expected - p2[c] as f32).abs() <= 1.0);
+            assert!(p2[c] >= p1[c]);
+        }
+        assert_eq!(p1[3], p2[3]);
+    }
+
+    // Out of range
+    let mut image = src.clone();
+    assert!(filter::gamma(&mut image, 0.0).is_err());
+}
+
+#[test]
+fn saturation_test() {
+    let src = raster::open("tests/in/sample.png").unwrap();
+
+    // 0.0 is grayscale
+    let mut image = src.clone();
+    filter::saturation(&mut image, 0.0).unwrap();
+    let mut gray = src.clone();
+    filter::grayscale(&mut gray).unwrap();
+    assert_eq!(gray.bytes, image.bytes);
+
+    // 1.0 is a no-op
+    let mut image = src.clone();
+    filter::saturation(&mut image, 1.0).unwrap();
+    assert_eq!(src.bytes, image.bytes);
+}
+
+#[test]
+fn hue_rotate_test() {
+    // Red becomes green
+    let mut image = Image::blank(4, 4);
+    editor::fill(&mut image, Color::red()).unwrap();
+    filter::hue_rotate(&mut image, 120.0).unwrap();
+    let p = image.get_pixel(0, 0).unwrap();
+    assert_eq!((0, 255, 0, 255), (p.r, p.g, p.b, p.a));
+
+    // 0 and 360 are near-identity
+    let src = raster::open("tests/in/sample.png").unwrap();
+    for degrees in &[0.0, 360.0] {
+        let mut image = src.clone();
+        filter::hue_rotate(&mut image, *degrees).unwrap();
+        for (p1, p2) in src.bytes.iter().zip(image.bytes.iter()) {
+            assert!((*p1 as i32 - *p2 as i32).abs() <= 1);
+        }
+    }
+}
+
+#[test]
+fn threshold_test() {
+    let mut image = Image::blank(256, 4);
+    for y in 0..image.height {
+        for x in 0..image.width {
+            image
+                .set_pixel(x, y, &Color::rgb(x as u8, x as u8, x as u8))
+                .unwrap();
+        }
+    }
+
+    filter::threshold(&mut image, 100).unwrap();
+    for x in 0..image.width {
+        let expected = if x >= 100 { 255 } else { 0 };
+        let p = image.get_pixel(x, 2).unwrap();
+        assert_eq!((expected, expected, expected, 255), (p.r, p.g, p.b, p.a));
+    }
+}
+
+#[test]
+fn posterize_test() {
+    let mut image = raster::open("tests/in/sample.png").unwrap();
+    filter::posterize(&mut image, 2).unwrap();
+    for pixel in image.bytes.chunks(4) {
+        for v in &pixel[0..3] {
+            assert!(*v == 0 || *v == 255);
+        }
+    }
+
+    assert!(filter::posterize(&mut image, 1).is_err());
+    assert!(filter::posterize(&mut image, 0).is_err());
+}
+
+#[test]
+fn box_blur_test() {
+    // Radius 0 is identity
+    let src = raster::open("tests/in/sample.png").unwrap();
+    let mut image = src.clone();
+    filter::box_blur(&mut image, 0).unwrap();
+    assert_eq!(src.bytes, image.bytes);
+
+    // Uniform stays uniform, even with a radius larger than the image
+    let mut image = Image::blank(7, 5);
+    editor::fill(&mut image, Color::rgba(10, 120, 230, 200)).unwrap();
+    let expected = image.bytes.clone();
+    filter::box_blur(&mut image, 3).unwrap();
+    assert_eq!(expected, image.bytes);
+    filter::box_blur(&mut image, 20).unwrap();
+    assert_eq!(expected, image.bytes);
+
+    // Averages the window
+    let mut image = Image::blank(3, 1);
+    editor::fill(&mut image, Color::black()).unwrap();
+    image.set_pixel(1, 0, &Color::rgb(255, 255, 255)).unwrap();
+    filter::box_blur(&mut image, 1).unwrap();
+    assert_eq!(85, image.get_pixel(0, 0).unwrap().r);
+    assert_eq!(85, image.get_pixel(1, 0).unwrap().r);
+
+    // Empty images are left as they are
+    for &(w, h) in &[(0, 3), (3, 0)] {
+        let mut empty = Image::blank(w, h);
+        filter::box_blur(&mut empty, 2).unwrap();
+        assert_eq!((w, h), (empty.width, empty.height));
+    }
+}
+
+#[test]
+fn gaussian_blur_test() {
+    let mut src = Image::blank(40, 30);
+    editor::fill(&mut src, Color::black()).unwrap();
+    for y in 8..14 {
+        for x in 20..27 {
+            src.set_pixel(x, y, &Color::white()).unwrap();
+        }
+    }
+
+    // Returns the total brightness and the center of mass
+    fn mass(image: &Image) -> (f32, f32, f32) {
+        let (mut total, mut cx, mut cy) = (0.0, 0.0, 0.0);
+        for y in 0..image.height {
+            for x in 0..image.width {
+                let v = image.get_pixel(x, y).unwrap().r as f32;
+                total += v;
+                cx += v * x as f32;
+                cy += v * y as f32;
+            }
+        }
+        (total, cx / total, cy / total)
+    }
+
+    let mut image = src.clone();
+    filter::gaussian_blur(&mut image, 2.0).unwrap();
+    let (total1, x1, y1) = mass(&src);
+    let (total2, x2, y2) = mass(&image);
+    assert!((x1 - x2).abs() < 0.1);
+    assert!((y1 - y2).abs() < 0.1);
+    assert!((total1 - total2).abs() / total1 < 0.02);
+    assert!(image.get_pixel(20, 8).unwrap().r < 255);
+
+    // Sigma near 0 is identity
+    let mut image = src.clone();
+    filter::gaussian_blur(&mut image, 0.1).unwrap();
+    assert_eq!(src.bytes, image.bytes);
+}
+
+#[test]
+fn unsharp_mask_test() {
+    // Step edge from dark gray to light gray
+    let mut src = Image::blank(20, 4);
+    for y in 0..src.height {
+        for x in 0..src.width {
+            let v = if x < 10 { 30 } else { 225 };
+            src.set_pixel(x, y, &Color::rgba(v, v, v, 200)).unwrap();
+        }
+    }
+
+    // 0.0 is identity
+    let mut image = src.clone();
+    filter::unsharp_mask(&mut image, 0.0).unwrap();
+    assert_eq!(src.bytes, image.bytes);
+
+    // Overshoot clamps instead of wrapping
+    let mut image = src.clone();
+    filter::unsharp_mask(&mut image, 10.0).unwrap();
+    let dark = image.get_pixel(9, 1).unwrap();
+    let light = image.get_pixel(10, 1).unwrap();
+    assert_eq!((0, 200), (dark.r, dark.a));
+    assert_eq!((255, 200), (light.r, light.a));
+    assert_eq!(30, image.get_pixel(0, 1).unwrap().r);
+    assert_eq!(225, image.get_pixel(19, 1).unwrap().r);
+}
+
+#[test]
+fn convolve_kernel_test() {
+    let src = raster::open("tests/in/sample.png").unwrap();
+
+    // Identity
+    let mut image = src.clone();
+    let kernel = [0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0];
+    filter::convolve_kernel(&mut image, &kernel, 3, 1.0, 0.0).unwrap();
+    assert_eq!(src.bytes, image.bytes);
+
+    // Edge detection is black on flat areas and bright on edges
+    let mut image = Image::blank(10, 10);
+    editor::fill(&mut image, Color::rgb(50, 50, 50)).unwrap();
+    for y in 0..10 {
+        for x in 5..10 {
+            image.set_pixel(x, y, &Color::rgb(200, 200, 200)).unwrap();
+        }
+    }
+    let kernel = [-1.0, -1.0, -1.0, -1.0, 8.0, -1.0, -1.0, -1.0, -1.0];
+    filter::convolve_kernel(&mut image, &kernel, 3, 1.0, 0.0).unwrap();
+    assert_eq!(0, image.get_pixel(1, 5).unwrap().r);
+    assert_eq!(0, image.get_pixel(8, 5).unwrap().r);
+    assert_eq!(255, image.get_pixel(5, 5).unwrap().r);
+
+    // Invalid kernels
+    assert!(filter::convolve_kernel(&mut image, &kernel, 2, 1.0, 0.0).is_err());
+    assert!(filter::convolve_kernel(&mut image, &kernel[..8], 3, 1.0, 0.0).is_err());
+}
+
+#[test]
+fn sobel_both_test() {
+    // No edges, all black
+    let mut image = Image::blank(10, 10);
+    editor::fill(&mut image, Color::rgba(90, 140, 30, 100)).unwrap();
+    filter::sobel(&mut image, Orientation::Both).unwrap();
+    for pixel in image.bytes.chunks(4) {
+        assert_eq!(&[0, 0, 0, 255], pixel);
+    }
+
+    // Both sides of a vertical seam light up
+    let seams = [
+        (Color::black(), Color::white()),
+        (Color::white(), Color::black()),
+    ];
+    for (left, right) in seams.iter() {
+        let mut image = Image::blank(10, 10);
+        editor::fill(&mut image, left.clone()).unwrap();
+        for y in 0..10 {
+            for x in 5..10 {
+                image.set_pixel(x, y, right).unwrap();
+            }
+        }
+        filter::sobel(&mut image, Orientation::Both).unwrap();
+        assert_eq!(0, image.get_pixel(1, 5).unwrap().r);
+        assert_eq!(255, image.get_pixel(4, 5).unwrap().r);
+        assert_eq!(255, image.get_pixel(5, 5).unwrap().r);
+        assert_eq!(0, image.get_pixel(8, 5).unwrap().r);
+    }
+}
+
+#[test]
+fn emboss_test() {
+    let mut image = raster::open("tests/in/sample.jpg").unwrap();
+    filter::emboss(&mut image).unwrap();
+    raster::save(&image, "tests/out/test_filter_emboss_relief.jpg").unwrap();
+
+    // Flat areas become gray
+    let mut image = Image::blank(10, 10);
+    editor::fill(&mut image, Color::rgba(200, 30, 90, 150)).unwrap();
+    filter::emboss(&mut image).unwrap();
+    for pixel in image.bytes.chunks(4) {
+        assert_eq!(&[128, 128, 128, 150], pixel);
+    }
+}
+
+#[test]
+fn equalize_test() {
+    // Low contrast gray ramp from 100 to 131
+    let mut image = Image::blank(32, 4);
+    for y in 0..4 {
+        for x in 0..32 {
+            let v = 100 + x as u8;
+            image.set_pixel(x, y, &Color::rgba(v, v, v, 200)).unwrap();
+        }
+    }
+
+    filter::equalize(&mut image).unwrap();
+    let histogram = image.histogram();
+    let min = histogram.luminance.iter().position(|&c| c > 0).unwrap();
+    let max = histogram.luminance.iter().rposition(|&c| c > 0).unwrap();
+    assert_eq!((0, 255), (min, max));
+    assert!(image.bytes.chunks(4).all(|p| p[3] == 200));
+
+    // Flat stays flat
+    let mut flat = Image::blank(8, 8);
+    editor::fill(&mut flat, Color::rgb(90, 90, 90)).unwrap();
+    let original = flat.clone();
+    filter::equalize(&mut flat).unwrap();
+    assert_eq!(original.bytes, flat.bytes);
+}
+
+#[test]
+fn quantize_test() {
+    let mut image = raster::open("tests/in/sample.jpg").unwrap();
+    filter::quantize(&mut image, 2).unwrap();
+
+    let mut colors: Vec<&[u8]> = image.bytes.chunks(4).map(|p| &p[0..3]).collect();
+    colors.sort();
+    colors.dedup();
+    assert!(colors.len() <= 2);
+
+    assert!(filter::quantize(&mut image, 0).is_err());
+}
+
+#[test]
+fn dither_test() {
+    // Gray ramp from 0 to 255
+    let mut image = Image::blank(256, 16);
+    for y in 0..16 {
+        for x in 0..256 {
+            let v = x as u8;
+            image.set_pixel(x, y, &Color::rgb(v, v, v)).unwrap();
+        }
+    }
+    let mut quantized = image.clone();
+    filter::quantize(&mut quantized, 2).unwrap();
+    filter::dither(&mut image, 2).unwrap();
+
+    let mut colors: Vec<&[u8]> = image.bytes.chunks(4).map(|p| &p[0..3]).collect();
+    colors.sort();
+    colors.dedup();
+    assert_eq!(2, colors.len());
+
+    // The 96 - 111 columns average 103.5, which a single palette color can't approximate
+    let average = |image: &Image| {
+        let region = image.get_region(96, 0, 16, 16).unwrap();
+        region.bytes.chunks(4).map(|p| p[0] as f32).sum::<f32>() / 256.0
+    };
+    assert!((average(&image) - 103.5).abs() < 6.0);
+    assert!((average(&quantized) - 103.5).abs() > 30.0);
+
+    assert!(filter::dither(&mut image, 0).is_err());
+}
+
+#[test]
+fn opacity_test() {
+    let mut image = raster::open("tests/in/sample.png").unwrap();
+    image.set_pixel(0, 0, &Color::rgba(10, 20, 30, 100)).unwrap();
+    let original = image.clone();
+
+    filter::opacity(&mut image, 0.5).unwrap();
+    for (p, q) in image.bytes.chunks(4).zip(original.bytes.chunks(4)) {
+        assert_eq!(&q[0..3], &p[0..3]);
+        assert_eq!((q[3] as f32 * 0.5).round() as u8, p[3]);
+    }
+    assert_eq!(50, image.get_pixel(0, 0).unwrap().a);
+}
+
+#[test]
+fn vignette_test() {
+    let mut image = Image::blank(41, 31);
+    editor::fill(&mut image, Color::rgb(200, 150, 100)).unwrap();
+    filter::vignette(&mut image, 0.9).unwrap();
+
+    let center = image.get_pixel(20, 15).unwrap();
+    assert_eq!((200, 150, 100, 255), (center.r, center.g, center.b, center.a));
+    let corner = image.get_pixel(0, 0).unwrap();
+    assert_eq!((20, 15, 10, 255), (corner.r, corner.g, corner.b, corner.a));
+    let edge = image.get_pixel(0, 15).unwrap();
+    assert!(edge.r < center.r && edge.r > corner.r);
+}
+
+#[test]
+fn add_noise_test() {
+    let mut original = Image::blank(32, 32);
+    editor::fill(&mut original, Color::rgba(128, 128, 128, 200)).unwrap();
+    let noisy = |seed| {
+        let mut image = original.clone();
+        filter::add_noise(&mut image, 0.1, seed).unwrap();
+        image
+    };
+
+    let image = noisy(7);
+    assert_eq!(image.bytes, noisy(7).bytes);
+    assert_ne!(image.bytes, noisy(8).bytes);
+    assert_ne!(original.bytes, image.bytes);
+    for p in image.bytes.chunks(4) {
+        assert!(p[0..3].iter().all(|&v| (102..=154).contains(&v)));
+        assert_eq!(200, p[3]);
+    }
+}
+
+#[test]
+fn motion_blur_test() {
+    // Left half black, right half white, and the bottom row red
+    let mut image = Image::blank(20, 10);
+    editor::fill(&mut image, Color::black()).unwrap();
+    for y in 0..10 {
+        for x in 10..20 {
+            image.set_pixel(x, y, &Color::white()).unwrap();
+        }
+    }
+    for x in 0..20 {
+        image.set_pixel(x, 9, &Color::red()).unwrap();
+    }
+    let original = image.clone();
+
+    let mut unchanged = image.clone();
+    filter::motion_blur(&mut unchanged, 0, 0.0).unwrap();
+    assert_eq!(original.bytes, unchanged.bytes);
+
+    filter::motion_blur(&mut image, 5, 0.0).unwrap();
+
+    // The vertical edge is smeared horizontally
+    let near_edge = image.get_pixel(9, 4).unwrap();
+    assert!(near_edge.r > 0 && near_edge.r < 255);
+    assert_eq!(0, image.get_pixel(5, 4).unwrap().r);
+    assert_eq!(255, image.get_pixel(15, 4).unwrap().r);
+
+    // The horizontal edge is not smeared vertically
+    for x in 0..20 {
+        let row_above = image.get_pixel(x, 8).unwrap();
+        let red = image.get_pixel(x, 9).unwrap();
+        assert_eq!(row_above.r, row_above.g);
+        assert_eq!((255, 0, 0), (red.r, red.g, red.b));
+    }
+}
+
+#[test]
+fn levels_test() {
+    let mut original = Image::blank(256, 1);
+    for x in 0..256 {
+        let v = x as u8;
+        original.set_pixel(x, 0, &Color::rgba(v, v, 255 - v, 100)).unwrap();
+    }
+
+    let mut image = original.clone();
+    filter::levels(&mut image, 0, 255, 1.0, 0, 255).unwrap();
+    assert_eq!(original.bytes, image.bytes);
+
+    let mut image = original.clone();
+    filter::levels(&mut image, 50, 255, 1.0, 0, 255).unwrap();
+    for x in 0..51 {
+        assert_eq!(0, image.get_pixel(x, 0).unwrap().r);
+    }
+    assert!(image.get_pixel(100, 0).unwrap().r < 100);
+    assert_eq!(255, image.get_pixel(255, 0).unwrap().r);
+    assert_eq!(100, image.get_pixel(10, 0).unwrap().a);
+
+    let mut image = original.clone();
+    assert!(filter::levels(&mut image, 0, 255, 0.0, 0, 255).is_err());
+}
@@ -0,0 +1,242 @@
+extern crate raster;
+
+use raster::error::RasterError;
+use raster::{Color, Image};
+
+#[test]
+fn from_raw_test() {
+    let bytes = [255, 0, 0, 255].repeat(4);
+    let image = Image::from_raw(2, 2, bytes).unwrap();
+
+    assert_eq!((2, 2), (image.width, image.height));
+    for y in 0..2 {
+        for x in 0..2 {
+            let p = image.get_pixel(x, y).unwrap();
+            assert_eq!((255, 0, 0, 255), (p.r, p.g, p.b, p.a));
+        }
+    }
+
+    match Image::from_raw(2, 2, vec![0; 15]) {
+        Err(RasterError::BufferSizeMismatch { expected, found }) => {
+            assert_eq!((16, 15), (expected, found));
+        }
+        _ => panic!("expected a buffer size mismatch"),
+    }
+}
+
+#[test]
+fn to_bytes_test() {
+    let image = raster::open("tests/in/sample.png").unwrap();
+    let bytes = image.to_bytes();
+    let p = image.get_pixel(0, 0).unwrap();
+
+    assert_eq!((image.width * image.height * 4) as usize, bytes.len());
+    assert_eq!(&[p.r, p.g, p.b, p.a], &bytes[0..4]);
+    assert_eq!(bytes.to_vec(), image.clone().into_bytes());
+}
+
+#[test]
+fn get_region_test() {
+    let image = raster::open("tests/in/sample.png").unwrap();
+    let (w, h) = (image.width / 2, image.height / 2);
+
+    // Bottom right quadrant
+    let quadrant = image.get_region(w, h, w, h).unwrap();
+    assert_eq!((w, h), (quadrant.width, quadrant.height));
+    for &(x, y) in &[(0, 0), (w - 1, 0), (0, h - 1), (w - 1, h - 1), (17, 42)] {
+        let p = quadrant.get_pixel(x, y).unwrap();
+        let q = image.get_pixel(w + x, h + y).unwrap();
+        assert_eq!((q.r, q.g, q.b, q.a), (p.r, p.g, p.b, p.a));
+    }
+
+    match image.get_region(w, h, w + 1, h) {
+        Err(RasterError::PixelOutOfBounds(x, y)) => assert_eq!((w * 2, h * 2 - 1), (x, y)),
+        _ => panic!("expected the region to be out of bounds"),
+    }
+}
+
+#[test]
+fn set_region_test() {
+    let image = raster::open("tests/in/sample.png").unwrap();
+    let (w, h) = (image.width / 2, image.height / 2);
+    let quadrant = image.get_region(0, 0, w, h).unwrap();
+
+    let mut canvas = Image::blank(image.width, image.height);
+    canvas.set_region(w, h, &quadrant).unwrap();
+    assert_eq!(quadrant.bytes, canvas.get_region(w, h, w, h).unwrap().bytes);
+    assert_eq!(Image::blank(w, h).bytes, canvas.get_region(0, 0, w, h).unwrap().bytes);
+
+    // Clipped on the top left
+    let mut clipped = Image::blank(w, h);
+    clipped.set_region(-10, -20, &quadrant).unwrap();
+    assert_eq!(
+        quadrant.get_region(10, 20, w - 10, h - 20).unwrap().bytes,
+        clipped.get_region(0, 0, w - 10, h - 20).unwrap().bytes
+    );
+}
+
+#[test]
+fn map_pixels_test() {
+    let image = raster::open("tests/in/sample.png").unwrap();
+
+    let mut expected = image.clone();
+    for y in 0..expected.height {
+        for x in 0..expected.width {
+            let p = expected.get_pixel(x, y).unwrap();
+            let inverted = Color::rgba(255 - p.r, 255 - p.g, 255 - p.b, p.a);
+            expected.set_pixel(x, y, &inverted).unwrap();
+        }
+    }
+
+    let mut inverted = image.clone();
+    inverted.map_pixels(|_, _, p| Color::rgba(255 - p.r, 255 - p.g, 255 - p.b, p.a));
+    assert_eq!(expected.bytes, inverted.bytes);
+
+    // Coordinates are row-major
+    let mut count = 0;
+    for (x, y, p) in image.pixels() {
+        assert_eq!((count % image.width, count / image.width), (x, y));
+        assert_eq!(image.get_pixel(x, y).unwrap().r, p.r);
+        count += 1;
+    }
+    assert_eq!(image.width * image.height, count);
+}
+
+#[test]
+fn histogram_test() {
+    // Two tones, 3 black pixels and 6 gray pixels
+    let mut image = Image::blank(3, 3);
+    for x in 0..3 {
+        for y in 1..3 {
+            image.set_pixel(x, y, &Color::rgb(100, 150, 200)).unwrap();
+        }
+    }
+
+    let histogram = image.histogram();
+    let luminance: Vec<(usize, u32)> = histogram
+        .luminance
+        .iter()
+        .cloned()
+        .enumerate()
+        .filter(|&(_, count)| count > 0)
+        .collect();
+    let gray = Color::rgb(100, 150, 200).luminance() as usize;
+    assert_eq!(vec![(0, 3), (gray, 6)], luminance);
+    assert_eq!((3, 6), (histogram.g[0], histogram.g[150]));
+    assert_eq!((100, 150, 200, gray as u8), histogram.peaks());
+}
+
+#[test]
+fn dominant_colors_test() {
+    // 70% blue and 30% red
+    let mut image = Image::blank(100, 100);
+    for y in 0..100 {
+        for x in 0..100 {
+            let color = if x < 70 { Color::blue() } else { Color::red() };
+            image.set_pixel(x, y, &color).unwrap();
+        }
+    }
+
+    let colors = image.dominant_colors(3);
+    let colors: Vec<(u8, u8, u8)> = colors.iter().map(|c| (c.r, c.g, c.b)).collect();
+    assert_eq!(vec![(0, 0, 255), (255, 0, 0)], colors);
+}
+
+#[test]
+fn premultiply_alpha_test() {
+    let mut image = Image::blank(2, 1);
+    image.set_pixel(0, 0, &Color::rgba(255, 255, 255, 128)).unwrap();
+    image.set_pixel(1, 0, &Color::rgba(10, 20, 30, 255)).unwrap();
+
+    image.premultiply_alpha();
+    assert_eq!(&[128, 128, 128, 128, 10, 20, 30, 255], image.to_bytes());
+
+    image.unpremultiply_alpha();
+    assert_eq!(&[255, 255, 255, 128, 10, 20, 30, 255], image.to_bytes());
+}
+
+#[test]
+fn compare_test() {
+    let image1 = raster::open("tests/in/sample.png").unwrap();
+    let mut image2 = image1.clone();
+    assert_eq!(0.0, image1.compare(&image2).unwrap());
+    assert!(image1.is_equal(&image2));
+
+    let pixel = image2.get_pixel(10, 10).unwrap();
+    image2
+        .set_pixel(10, 10, &Color::rgba(255 - pixel.r, pixel.g, pixel.b, pixel.a))
+        .unwrap();
+    let diff = image1.compare(&image2).unwrap();
+    assert!(diff > 0.0 && diff < 0.0001);
+    assert!(!image1.is_equal(&image2));
+
+    match image1.compare(&Image::blank(3, 3)) {
+        Err(RasterError::DimensionMismatch { expected, found }) => {
+            assert_eq!((image1.width, image1.height), expected);
+            assert_eq!((3, 3), found);
+        }
+        _ => panic!("expected DimensionMismatch"),
+    }
+    assert!(!Image::blank(3, 3).is_equal(&Image::blank(3, 4)));
+}
+
+#[test]
+fn extract_channel_test() {
+    let mut image = Image::blank(3, 2);
+    image.map_pixels(|_, _, _| Color::red());
+
+    let red = image.extract_channel("red").unwrap();
+    assert_eq!((3, 2), (red.width, red.height));
+    assert!(red.bytes.iter().all(|&v| v == 255));
+    let green = image.extract_channel("green").unwrap();
+    assert!(green.bytes.chunks(4).all(|p| p == [0, 0, 0, 255]));
+
+    match image.extract_channel("purple") {
+        Err(RasterError::InvalidChannel(name)) => assert_eq!("purple", name),
+        _ => panic!("expected InvalidChannel"),
+    }
+}
+
+#[test]
+fn split_channels_test() {
+    let mut image = raster::open("tests/in/sample.png").unwrap();
+    image.map_pixels(|x, y, p| Color::rgba(p.r, p.g, p.b, ((x + y) % 256) as u8));
+
+    let channels = image.split_channels();
+    assert!(channels[0].is_equal(&image.extract_channel("red").unwrap()));
+    assert!(channels[3].is_equal(&image.extract_channel("alpha").unwrap()));
+
+    let [r, g, b, a] = channels;
+    let merged = Image::merge_channels(&r, &g, &b, &a).unwrap();
+    assert!(merged.is_equal(&image));
+
+    assert!(Image::merge_channels(&r, &g, &b, &Image::blank(2, 2)).is_err());
+}
+
+#[test]
+fn dimension_mismatch_test() {
+    let image = Image::blank(4, 3);
+    let err = image.compare(&Image::blank(3, 4)).unwrap_err();
+    match err {
+        RasterError::DimensionMismatch { expected, found } => {
+            assert_eq!((4, 3), expected);
+            assert_eq!((3, 4), found);
+        }
+        _ => panic!("expected DimensionMismatch"),
+    }
+    assert_eq!("expected a 4x3 image, found 3x4", err.to_string());
+}
+
+#[test]
+fn blank_with_color_test() {
+    let image = Image::blank_with_color(10, 10, Color::red());
+    assert_eq!((10, 10), (image.width, image.height));
+    assert_eq!(400, image.bytes.len());
+    for (_, _, p) in image.pixels() {
+        assert_eq!((255, 0, 0, 255), (p.r, p.g, p.b, p.a));
+    }
+
+    let mut filled = Image::blank(10, 10);
+    raster::editor::fill(&mut filled, Color::red()).unwrap();
+    assert!(filled.is_equal(&image));
+}
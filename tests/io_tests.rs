@@ -1,3 +1,4 @@
+extern crate png;
 extern crate raster;
 
 #[test]
@@ -123,3 +124,337 @@ fn read_png_format_fail() {
         }
     });
 }
+
+#[test]
+fn save_with_quality_test() {
+    let image = raster::open("tests/in/sample.jpg").unwrap();
+    raster::save_with_quality(&image, "tests/out/test_quality_10.jpg", 10).unwrap();
+    raster::save_with_quality(&image, "tests/out/test_quality_95.jpg", 95).unwrap();
+    let low = std::fs::metadata("tests/out/test_quality_10.jpg").unwrap().len();
+    let high = std::fs::metadata("tests/out/test_quality_95.jpg").unwrap().len();
+    assert!(low * 3 < high);
+
+    // Out of range values are clamped
+    raster::save_with_quality(&image, "tests/out/test_quality_0.jpg", 0).unwrap();
+    raster::save_with_quality(&image, "tests/out/test_quality_255.jpg", 255).unwrap();
+
+    // Ignored for other formats
+    raster::save_with_quality(&image, "tests/out/test_quality.png", 10).unwrap();
+    let png = raster::open("tests/out/test_quality.png").unwrap();
+    assert_eq!(image.bytes, png.bytes);
+}
+
+#[test]
+fn save_to_bytes_test() {
+    let image = raster::open("tests/in/sample.png").unwrap();
+    for name in &["gif", "jpg", "png"] {
+        let bytes = raster::save_to_bytes(&image, name.parse().unwrap()).unwrap();
+        let path = format!("tests/out/test_save_to_bytes.{}", name);
+        std::fs::write(&path, &bytes).unwrap();
+        let decoded = raster::open(&path).unwrap();
+        assert_eq!((image.width, image.height), (decoded.width, decoded.height));
+    }
+
+    // PNG is lossless
+    let bytes = raster::save_to_bytes(&image, raster::ImageFormat::Png).unwrap();
+    std::fs::write("tests/out/test_save_to_bytes_lossless.png", &bytes).unwrap();
+    let decoded = raster::open("tests/out/test_save_to_bytes_lossless.png").unwrap();
+    assert_eq!(image.bytes, decoded.bytes);
+
+    match "webm".parse::<raster::ImageFormat>() {
+        Err(raster::error::RasterError::UnsupportedFormat(_)) => {}
+        _ => panic!("expected UnsupportedFormat"),
+    }
+}
+
+#[test]
+fn open_from_bytes_test() {
+    for path in &[
+        "tests/in/sample.gif",
+        "tests/in/sample.jpg",
+        "tests/in/sample.png",
+    ] {
+        let data = std::fs::read(path).unwrap();
+        let from_bytes = raster::open_from_bytes(&data).unwrap();
+        let from_file = raster::open(path).unwrap();
+        assert_eq!(from_file.width, from_bytes.width);
+        assert_eq!(from_file.height, from_bytes.height);
+        assert_eq!(from_file.bytes, from_bytes.bytes);
+    }
+
+    assert!(raster::open_from_bytes(b"not an image").is_err());
+    assert!(raster::open_from_bytes(&[]).is_err());
+}
+
+#[test]
+fn bmp_test() {
+    let image = raster::open("tests/in/sample.bmp").unwrap();
+    assert_eq!((1, 1), (image.width, image.height));
+
+    // Round trip, BMP is lossless
+    let image = raster::open("tests/in/sample.png").unwrap();
+    raster::save(&image, "tests/out/test_save.bmp").unwrap();
+    let bmp = raster::open("tests/out/test_save.bmp").unwrap();
+    assert_eq!((image.width, image.height), (bmp.width, bmp.height));
+    assert_eq!(image.bytes, bmp.bytes);
+
+    let data = std::fs::read("tests/out/test_save.bmp").unwrap();
+    assert_eq!(bmp.bytes, raster::open_from_bytes(&data).unwrap().bytes);
+
+    // Alpha is kept
+    let translucent = raster::Image::blank_with_color(3, 2, raster::Color::rgba(10, 20, 30, 40));
+    let data = raster::save_to_bytes(&translucent, raster::ImageFormat::Bmp).unwrap();
+    assert_eq!(translucent.bytes, raster::open_from_bytes(&data).unwrap().bytes);
+}
+
+#[test]
+fn webp_test() {
+    // Lossy WebP of sample.jpg, whose 500x281 size is not a multiple of the 16px macroblocks
+    let image = raster::open("tests/in/sample.webp").unwrap();
+    let jpeg = raster::open("tests/in/sample.jpg").unwrap();
+    assert_eq!((jpeg.width, jpeg.height), (image.width, image.height));
+    assert!(image.compare(&jpeg).unwrap() < 0.005);
+    // Corners, including the partial macroblocks at the right and bottom edges, keep their color
+    for &(x, y) in &[(0, 0), (499, 0), (0, 280), (499, 280), (250, 140)] {
+        let p = image.get_pixel(x, y).unwrap();
+        let q = jpeg.get_pixel(x, y).unwrap();
+        assert!((p.r as i32 - q.r as i32).abs() <= 16);
+        assert!((p.g as i32 - q.g as i32).abs() <= 16);
+        assert!((p.b as i32 - q.b as i32).abs() <= 16);
+        assert_eq!(255, p.a);
+    }
+
+    let data = std::fs::read("tests/in/sample.webp").unwrap();
+    assert_eq!(image.bytes, raster::open_from_bytes(&data).unwrap().bytes);
+
+    // Encoding is not supported and leaves an existing file untouched
+    std::fs::write("tests/out/test_save.webp", b"existing").unwrap();
+    match raster::save(&image, "tests/out/test_save.webp") {
+        Err(raster::error::RasterError::UnsupportedFormat(_)) => {}
+        _ => panic!("expected UnsupportedFormat"),
+    }
+    assert_eq!(b"existing".to_vec(), std::fs::read("tests/out/test_save.webp").unwrap());
+}
+
+#[test]
+fn open_frames_test() {
+    let frames = raster::open_frames("tests/in/animated.gif").unwrap();
+    assert_eq!(3, frames.len());
+    for frame in &frames {
+        assert_eq!((11, 29), (frame.width, frame.height));
+        assert_eq!(11 * 29 * 4, frame.bytes.len());
+    }
+    assert_ne!(frames[0].bytes, frames[1].bytes);
+
+    // The first frame matches open
+    let image = raster::open("tests/in/animated.gif").unwrap();
+    assert_eq!(image.bytes, frames[0].bytes);
+
+    // Other formats give a single frame
+    let frames = raster::open_frames("tests/in/sample.png").unwrap();
+    assert_eq!(1, frames.len());
+}
+
+#[test]
+fn save_frames_test() {
+    let colors = vec![
+        raster::Color::red(),
+        raster::Color::green(),
+        raster::Color::blue(),
+    ];
+    let mut frames = Vec::new();
+    for color in colors {
+        let mut frame = raster::Image::blank(8, 6);
+        raster::editor::fill(&mut frame, color).unwrap();
+        frames.push(frame);
+    }
+    raster::save_frames(&frames, "tests/out/test_save_frames_3.gif", 100).unwrap();
+
+    let opened = raster::open_frames("tests/out/test_save_frames_3.gif").unwrap();
+    assert_eq!(3, opened.len());
+    for (frame, expected) in opened.iter().zip(&frames) {
+        assert_eq!((8, 6), (frame.width, frame.height));
+        assert_eq!(expected.bytes, frame.bytes);
+    }
+
+    frames.push(raster::Image::blank(6, 8));
+    match raster::save_frames(&frames, "tests/out/test_save_frames_bad.gif", 100) {
+        Err(raster::error::RasterError::DimensionMismatch { expected, found }) => {
+            assert_eq!((8, 6), expected);
+            assert_eq!((6, 8), found);
+        }
+        _ => panic!("expected DimensionMismatch"),
+    }
+
+    // An empty list leaves an existing file untouched
+    let out = "tests/out/test_save_frames_empty.gif";
+    std::fs::write(out, b"existing").unwrap();
+    match raster::save_frames(&[], out, 100) {
+        Err(raster::error::RasterError::Encode(raster::ImageFormat::Gif, _)) => {}
+        _ => panic!("expected Encode"),
+    }
+    assert_eq!(b"existing".to_vec(), std::fs::read(out).unwrap());
+}
+
+#[test]
+fn exif_orientation_test() {
+    // Stored as 40x20, red on the left and blue on the right. Orientation 6 means the camera was
+    // rotated so the image must be turned 90 degrees clockwise to display it upright.
+    let raw = raster::open_no_rotate("tests/in/exif-orientation-6.jpg").unwrap();
+    assert_eq!((40, 20), (raw.width, raw.height));
+    assert!(raw.get_pixel(5, 10).unwrap().r > 200);
+
+    let image = raster::open("tests/in/exif-orientation-6.jpg").unwrap();
+    assert_eq!((20, 40), (image.width, image.height));
+    assert!(image.get_pixel(10, 5).unwrap().r > 200);
+    assert!(image.get_pixel(10, 35).unwrap().b > 200);
+
+    let data = std::fs::read("tests/in/exif-orientation-6.jpg").unwrap();
+    let from_bytes = raster::open_from_bytes(&data).unwrap();
+    assert_eq!((20, 40), (from_bytes.width, from_bytes.height));
+    assert_eq!(image.bytes, from_bytes.bytes);
+
+    // Orientation 1 is left alone
+    let raw = raster::open_no_rotate("tests/in/sample.jpg").unwrap();
+    let image = raster::open("tests/in/sample.jpg").unwrap();
+    assert_eq!(raw.bytes, image.bytes);
+}
+
+#[test]
+fn read_metadata_test() {
+    let metadata = raster::read_metadata("tests/in/exif-orientation-6.jpg").unwrap();
+    assert_eq!(Some(6), metadata.orientation);
+    assert_eq!(Some("Raster Camera Co.".to_string()), metadata.make);
+    assert_eq!(Some("RC-1000".to_string()), metadata.model);
+    assert_eq!(Some("2018:06:17 10:30:00".to_string()), metadata.datetime);
+    assert_eq!(Some((1, 250)), metadata.exposure_time);
+
+    // Little endian EXIF without camera info
+    let metadata = raster::read_metadata("tests/in/sample.jpg").unwrap();
+    assert_eq!(Some(1), metadata.orientation);
+    assert_eq!(Some("2016:12:16 06:49:56".to_string()), metadata.datetime);
+    assert_eq!(None, metadata.make);
+
+    // No EXIF
+    let metadata = raster::read_metadata("tests/in/sample.png").unwrap();
+    assert_eq!(raster::Metadata::default(), metadata);
+}
+
+#[test]
+fn save_png_test() {
+    let image = raster::open("tests/in/sample.jpg").unwrap();
+    let size = |compression, out: &str| {
+        raster::save_png(&image, out, compression).unwrap();
+        let saved = raster::open(out).unwrap();
+        assert_eq!(image.bytes, saved.bytes);
+        std::fs::metadata(out).unwrap().len()
+    };
+
+    let fast = size(raster::PngCompression::Fast, "tests/out/test_save_png_fast.png");
+    let default = size(raster::PngCompression::Default, "tests/out/test_save_png_default.png");
+    let best = size(raster::PngCompression::Best, "tests/out/test_save_png_best.png");
+    assert!(best <= default && default <= fast);
+
+    // Fast compression, which save uses, gives the same bytes as the png crate's own writer
+    let mut expected = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut expected, image.width as u32, image.height as u32);
+        png::HasParameters::set(&mut encoder, png::ColorType::RGBA);
+        png::HasParameters::set(&mut encoder, png::BitDepth::Eight);
+        encoder.write_header().unwrap().write_image_data(&image.bytes).unwrap();
+    }
+    assert_eq!(expected, std::fs::read("tests/out/test_save_png_fast.png").unwrap());
+    raster::save(&image, "tests/out/test_save_png_save.png").unwrap();
+    assert_eq!(expected, std::fs::read("tests/out/test_save_png_save.png").unwrap());
+
+    // An invalid image leaves an existing file untouched
+    let out = "tests/out/test_save_png_invalid.png";
+    std::fs::write(out, b"existing").unwrap();
+    let invalid = raster::Image {
+        width: 10,
+        height: 10,
+        bytes: vec![0; 3],
+    };
+    assert!(raster::save_png(&invalid, out, raster::PngCompression::Best).is_err());
+    assert_eq!(b"existing".to_vec(), std::fs::read(out).unwrap());
+}
+
+#[test]
+fn save_strips_metadata_test() {
+    let image = raster::open("tests/in/exif-orientation-6.jpg").unwrap();
+    raster::save(&image, "tests/out/test_save_strips_metadata.jpg").unwrap();
+
+    let metadata = raster::read_metadata("tests/out/test_save_strips_metadata.jpg").unwrap();
+    assert_eq!(raster::Metadata::default(), metadata);
+    let data = std::fs::read("tests/out/test_save_strips_metadata.jpg").unwrap();
+    assert!(!data.windows(6).any(|w| w == b"Exif\0\0"));
+}
+
+#[test]
+fn save_as_test() {
+    let image = raster::open("tests/in/sample.png").unwrap();
+    raster::save_as(&image, "tests/out/test_save_as.dat", raster::ImageFormat::Png).unwrap();
+
+    let data = std::fs::read("tests/out/test_save_as.dat").unwrap();
+    assert!(data.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]));
+    let decoded = raster::open_from_bytes(&data).unwrap();
+    assert_eq!(image.bytes, decoded.bytes);
+
+    // WebP can't be encoded and leaves an existing file untouched
+    let out = "tests/out/test_save_as_webp.dat";
+    std::fs::write(out, b"existing").unwrap();
+    match raster::save_as(&image, out, raster::ImageFormat::Webp) {
+        Err(raster::error::RasterError::UnsupportedFormat(_)) => {}
+        _ => panic!("expected UnsupportedFormat"),
+    }
+    assert_eq!(b"existing".to_vec(), std::fs::read(out).unwrap());
+}
+
+#[test]
+fn resize_file_test() {
+    // 400x200 of 8x8 blocks, so that each block shrinks to exactly 1 pixel
+    let mut image = raster::Image::blank(400, 200);
+    image.map_pixels(|x, y, _| {
+        let (bx, by) = (x / 8, y / 8);
+        raster::Color::rgba((bx * 5) as u8, (by * 10) as u8, ((bx + by) * 3) as u8, 255 - by as u8)
+    });
+    raster::save(&image, "tests/out/test_resize_file_source.png").unwrap();
+
+    raster::resize_file(
+        "tests/out/test_resize_file_source.png",
+        "tests/out/test_resize_file_streamed.png",
+        50,
+        50,
+        raster::ResizeMode::Fit,
+    )
+    .unwrap();
+    let streamed = raster::open("tests/out/test_resize_file_streamed.png").unwrap();
+    let mut expected = image.clone();
+    let nearest = raster::InterpolationMode::Nearest;
+    raster::editor::resize_with(&mut expected, 50, 25, raster::ResizeMode::Exact, nearest).unwrap();
+    assert!(streamed.is_equal(&expected));
+
+    // Fill crops the excess
+    raster::resize_file(
+        "tests/out/test_resize_file_source.png",
+        "tests/out/test_resize_file_fill.png",
+        20,
+        25,
+        raster::ResizeMode::Fill,
+    )
+    .unwrap();
+    let fill = raster::open("tests/out/test_resize_file_fill.png").unwrap();
+    assert_eq!((20, 25), (fill.width, fill.height));
+
+    // Enlarging and other formats are resized in memory
+    raster::resize_file(
+        "tests/in/sample.jpg",
+        "tests/out/test_resize_file.jpg",
+        100,
+        100,
+        raster::ResizeMode::Exact,
+    )
+    .unwrap();
+    let jpeg = raster::open("tests/out/test_resize_file.jpg").unwrap();
+    assert_eq!((100, 100), (jpeg.width, jpeg.height));
+}
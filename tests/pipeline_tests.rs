@@ -0,0 +1,36 @@
+extern crate raster;
+
+use raster::{editor, Color, Image, Pipeline, PositionMode, ResizeMode};
+
+#[test]
+fn pipeline_test() {
+    let image = raster::open("tests/in/sample.jpg").unwrap();
+
+    let mut expected = image.clone();
+    editor::resize(&mut expected, 200, 200, ResizeMode::Fit).unwrap();
+    editor::fill(&mut expected, Color::rgba(10, 20, 30, 255)).unwrap();
+    editor::crop(&mut expected, 50, 40, PositionMode::Center, 5, 0).unwrap();
+
+    let mut pipeline = Pipeline::new(image);
+    pipeline
+        .resize(200, 200, ResizeMode::Fit)
+        .unwrap()
+        .fill(Color::rgba(10, 20, 30, 255))
+        .unwrap()
+        .crop(50, 40, PositionMode::Center, 5, 0)
+        .unwrap();
+    assert!(pipeline.image().is_equal(&expected));
+
+    let image: Image = pipeline.into_image();
+    assert_eq!((50, 40), (image.width, image.height));
+}
+
+#[test]
+fn pipeline_open_test() {
+    assert!(Pipeline::open("tests/in/not-a-png.png").is_err());
+
+    let mut pipeline = Pipeline::open("tests/in/sample.png").unwrap();
+    pipeline.grayscale().unwrap().save("tests/out/test_pipeline.png").unwrap();
+    let saved = raster::open("tests/out/test_pipeline.png").unwrap();
+    assert!(saved.is_equal(pipeline.image()));
+}
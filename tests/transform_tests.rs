@@ -0,0 +1,290 @@
+extern crate raster;
+
+use raster::{interpolate, transform, Color, Image, InterpolationMode, TransformMode};
+
+#[test]
+fn rotate90_test() {
+    let src = raster::open("tests/in/sample.png").unwrap();
+
+    let mut image = src.clone();
+    transform::rotate90(&mut image).unwrap();
+    assert_eq!((src.height, src.width), (image.width, image.height));
+    for _ in 0..3 {
+        transform::rotate90(&mut image).unwrap();
+    }
+    assert_eq!((src.width, src.height), (image.width, image.height));
+    assert_eq!(src.bytes, image.bytes);
+
+    // 90 then 270 is identity, 180 twice is identity
+    let mut image = src.clone();
+    transform::rotate90(&mut image).unwrap();
+    transform::rotate270(&mut image).unwrap();
+    assert_eq!(src.bytes, image.bytes);
+    transform::rotate180(&mut image).unwrap();
+    transform::rotate180(&mut image).unwrap();
+    assert_eq!(src.bytes, image.bytes);
+}
+
+#[test]
+fn rotate90_corners_test() {
+    // Red top left, blue top right on a 3x2 image
+    let mut image = Image::blank(3, 2);
+    image.set_pixel(0, 0, &Color::red()).unwrap();
+    image.set_pixel(2, 0, &Color::blue()).unwrap();
+
+    let mut rotated = image.clone();
+    transform::rotate90(&mut rotated).unwrap();
+    assert_eq!((2, 3), (rotated.width, rotated.height));
+    assert_eq!(255, rotated.get_pixel(1, 0).unwrap().r);
+    assert_eq!(255, rotated.get_pixel(1, 2).unwrap().b);
+
+    let mut rotated = image.clone();
+    transform::rotate180(&mut rotated).unwrap();
+    assert_eq!(255, rotated.get_pixel(2, 1).unwrap().r);
+    assert_eq!(255, rotated.get_pixel(0, 1).unwrap().b);
+
+    let mut rotated = image.clone();
+    transform::rotate270(&mut rotated).unwrap();
+    assert_eq!(255, rotated.get_pixel(0, 2).unwrap().r);
+    assert_eq!(255, rotated.get_pixel(0, 0).unwrap().b);
+}
+
+#[test]
+fn rotate_test() {
+    let src = raster::open("tests/in/sample.png").unwrap();
+
+    // 0 is identity
+    let mut image = src.clone();
+    transform::rotate(&mut image, 0.0, Color::black()).unwrap();
+    assert_eq!((src.width, src.height), (image.width, image.height));
+    assert_eq!(src.bytes, image.bytes);
+
+    // 360 is near-identity
+    let mut image = src.clone();
+    transform::rotate(&mut image, 360.0, Color::black()).unwrap();
+    assert_eq!((src.width, src.height), (image.width, image.height));
+    for (p1, p2) in src.bytes.iter().zip(image.bytes.iter()) {
+        assert!((*p1 as i32 - *p2 as i32).abs() <= 2);
+    }
+
+    // 90 swaps the dimensions
+    let mut image = src.clone();
+    transform::rotate(&mut image, 90.0, Color::black()).unwrap();
+    assert_eq!((src.height, src.width), (image.width, image.height));
+
+    // 45 enlarges the canvas and fills the corners
+    let mut image = Image::blank(10, 10);
+    raster::editor::fill(&mut image, Color::white()).unwrap();
+    transform::rotate(&mut image, 45.0, Color::rgb(255, 0, 0)).unwrap();
+    assert_eq!((15, 15), (image.width, image.height));
+    let corner = image.get_pixel(0, 0).unwrap();
+    assert_eq!((255, 0, 0), (corner.r, corner.g, corner.b));
+    let center = image.get_pixel(7, 7).unwrap();
+    assert_eq!((255, 255, 255), (center.r, center.g, center.b));
+
+    // Empty images stay empty
+    for &(w, h) in &[(0, 3), (3, 0), (0, 0)] {
+        let mut empty = Image::blank(w, h);
+        transform::rotate(&mut empty, 30.0, Color::black()).unwrap();
+        assert_eq!((w, h), (empty.width, empty.height));
+    }
+
+    for &degree in &[f32::NAN, f32::INFINITY] {
+        let mut image = src.clone();
+        match transform::rotate(&mut image, degree, Color::black()) {
+            Err(raster::error::RasterError::InvalidAngle(_)) => {}
+            _ => panic!("expected InvalidAngle"),
+        }
+    }
+}
+
+#[test]
+fn flip_test() {
+    // Odd and even widths
+    for path in &["tests/in/sample.png", "tests/in/flip_x_even.png"] {
+        let src = raster::open(path).unwrap();
+        for mode in &["horizontal", "vertical"] {
+            let mut image = src.clone();
+            transform::flip(&mut image, mode.parse().unwrap()).unwrap();
+            transform::flip(&mut image, mode.parse().unwrap()).unwrap();
+            assert_eq!(src.bytes, image.bytes);
+        }
+    }
+
+    let mut image = Image::blank(3, 2);
+    image.set_pixel(0, 0, &Color::red()).unwrap();
+
+    let mut flipped = image.clone();
+    transform::flip(&mut flipped, TransformMode::Horizontal).unwrap();
+    assert_eq!(255, flipped.get_pixel(2, 0).unwrap().r);
+
+    let mut flipped = image.clone();
+    transform::flip(&mut flipped, TransformMode::Vertical).unwrap();
+    assert_eq!(255, flipped.get_pixel(0, 1).unwrap().r);
+
+    assert!("diagonal".parse::<TransformMode>().is_err());
+
+    // Empty images are left as they are
+    for &(w, h) in &[(0, 3), (3, 0), (0, 0)] {
+        let mut empty = Image::blank(w, h);
+        transform::flip(&mut empty, TransformMode::Horizontal).unwrap();
+        transform::flip(&mut empty, TransformMode::Vertical).unwrap();
+        assert_eq!((w, h), (empty.width, empty.height));
+    }
+}
+
+// The rows are computed in parallel with the rayon feature, serially without it. Both must
+// match the same expected images.
+#[test]
+fn resize_parallel_test() {
+    let src = raster::open("tests/in/sample.jpg").unwrap();
+
+    for &(w, h) in &[(200, 133), (31, 300)] {
+        let mut image = src.clone();
+        transform::resize_exact(&mut image, w, h).unwrap();
+        let expected = raster::open(&format!("tests/in/resize-{}x{}.png", w, h)).unwrap();
+        assert_eq!((w, h), (image.width, image.height));
+        assert_eq!(expected.bytes, image.bytes);
+    }
+}
+
+#[test]
+fn resize_lanczos3_test() {
+    // Checkerboard of 1px squares, which aliases badly when sampled
+    let mut checkerboard = Image::blank(64, 64);
+    for y in 0..64 {
+        for x in 0..64 {
+            if (x + y) % 2 != 1 {
+                checkerboard.set_pixel(x, y, &Color::white()).unwrap();
+            }
+        }
+    }
+    let variance = |image: &Image| {
+        let values: Vec<f32> = image.bytes.chunks(4).map(|p| p[0] as f32).collect();
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / values.len() as f32
+    };
+
+    let mut nearest = checkerboard.clone();
+    interpolate::resample(&mut nearest, 10, 10, InterpolationMode::Nearest).unwrap();
+    let mut lanczos = checkerboard.clone();
+    interpolate::resample(&mut lanczos, 10, 10, InterpolationMode::Lanczos3).unwrap();
+
+    assert_eq!((10, 10), (lanczos.width, lanczos.height));
+    assert!(variance(&lanczos) * 10.0 < variance(&nearest));
+
+    // Flat areas keep their color
+    let mut flat = Image::blank(30, 20);
+    raster::editor::fill(&mut flat, Color::rgba(200, 100, 50, 255)).unwrap();
+    interpolate::resample(&mut flat, 7, 45, InterpolationMode::Lanczos3).unwrap();
+    assert!(flat.bytes.chunks(4).all(|p| p == [200, 100, 50, 255]));
+
+    // Empty sources have nothing to sample
+    for &(w, h) in &[(0, 5), (5, 0)] {
+        let mut empty = Image::blank(w, h);
+        interpolate::resample(&mut empty, 3, 2, InterpolationMode::Lanczos3).unwrap();
+        assert_eq!((3, 2), (empty.width, empty.height));
+        assert!(empty.bytes.iter().all(|&v| v == 0));
+    }
+}
+
+#[test]
+fn affine_test() {
+    let image = raster::open("tests/in/sample.png").unwrap();
+
+    let mut identity = image.clone();
+    transform::affine(&mut identity, [1.0, 0.0, 0.0, 0.0, 1.0, 0.0], Color::red()).unwrap();
+    assert!(identity.is_equal(&image));
+
+    // Translation keeps the original area, shifts the content and exposes the background
+    let mut moved = image.clone();
+    transform::affine(&mut moved, [1.0, 0.0, 10.0, 0.0, 1.0, 5.0], Color::red()).unwrap();
+    assert_eq!((image.width + 10, image.height + 5), (moved.width, moved.height));
+    assert!(moved.get_region(10, 5, image.width, image.height).unwrap().is_equal(&image));
+    let corner = moved.get_pixel(3, 2).unwrap();
+    assert_eq!((255, 0, 0, 255), (corner.r, corner.g, corner.b, corner.a));
+
+    // Scaling by 2 interpolates between the source pixels
+    let mut scaled = Image::blank(2, 2);
+    scaled.set_pixel(1, 1, &Color::white()).unwrap();
+    transform::affine(&mut scaled, [2.0, 0.0, 0.0, 0.0, 2.0, 0.0], Color::black()).unwrap();
+    assert_eq!((4, 4), (scaled.width, scaled.height));
+    assert_eq!(0, scaled.get_pixel(0, 0).unwrap().r);
+    assert!(scaled.get_pixel(1, 1).unwrap().r < 128);
+    assert!(scaled.get_pixel(2, 2).unwrap().r > 128);
+
+    let mut flat = image.clone();
+    match transform::affine(&mut flat, [1.0, 2.0, 0.0, 2.0, 4.0, 0.0], Color::red()) {
+        Err(raster::error::RasterError::InvalidMatrix) => {}
+        _ => panic!("expected InvalidMatrix"),
+    }
+
+    // Empty images stay empty
+    for &(w, h) in &[(0, 3), (3, 0), (0, 0)] {
+        let mut empty = Image::blank(w, h);
+        transform::affine(&mut empty, [1.0, 0.0, 2.0, 0.0, 1.0, 3.0], Color::red()).unwrap();
+        assert_eq!((w, h), (empty.width, empty.height));
+    }
+}
+
+#[test]
+fn perspective_test() {
+    // White with a black vertical line in the middle
+    let mut image = Image::blank_with_color(101, 101, Color::white());
+    for y in 0..101 {
+        image.set_pixel(50, y, &Color::black()).unwrap();
+    }
+    let corners = |w: f32, h: f32| [(0.0, 0.0), (w, 0.0), (w, h), (0.0, h)];
+
+    let mut unchanged = image.clone();
+    transform::perspective(&mut unchanged, corners(101.0, 101.0), Color::red()).unwrap();
+    assert!(unchanged.is_equal(&image));
+
+    // Pull the bottom right corner inward
+    let quad = [(0.0, 0.0), (101.0, 0.0), (70.0, 70.0), (0.0, 101.0)];
+    let mut warped = image.clone();
+    transform::perspective(&mut warped, quad, Color::red()).unwrap();
+    assert_eq!((101, 101), (warped.width, warped.height));
+    let outside = warped.get_pixel(95, 95).unwrap();
+    assert_eq!((255, 0, 0), (outside.r, outside.g, outside.b));
+
+    // The background is as red as the white, so the least red pixel of each row is on the line,
+    // which must stay straight
+    let mut line = Vec::new();
+    for y in 5..60 {
+        let x = (0..warped.width)
+            .min_by_key(|&x| warped.get_pixel(x, y).unwrap().r)
+            .unwrap();
+        line.push((x as f32, y as f32));
+    }
+    let (x0, y0) = line[0];
+    let (x1, y1) = line[line.len() - 1];
+    assert!(x1 < x0);
+    for &(x, y) in &line {
+        let expected = x0 + (x1 - x0) * (y - y0) / (y1 - y0);
+        assert!((x - expected).abs() <= 1.0);
+    }
+
+    // The canvas origin is on the other side of the horizon from this quad
+    let mut tilted = Image::blank_with_color(64, 64, Color::white());
+    let quad = [(6.0, 43.5), (12.7, 33.2), (58.4, 5.0), (67.8, 36.3)];
+    transform::perspective(&mut tilted, quad, Color::black()).unwrap();
+    let centroid = tilted.get_pixel(36 - 6, 29 - 5).unwrap();
+    assert_eq!((255, 255, 255), (centroid.r, centroid.g, centroid.b));
+
+    // Collinear, concave, crossed and mirrored corners
+    let invalid = [
+        [(0.0, 0.0), (50.0, 0.0), (100.0, 0.0), (0.0, 100.0)],
+        [(60.0, 60.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)],
+        [(0.0, 0.0), (100.0, 100.0), (100.0, 0.0), (0.0, 100.0)],
+        [(0.0, 0.0), (0.0, 100.0), (100.0, 100.0), (100.0, 0.0)],
+    ];
+    for quad in &invalid {
+        let mut bad = image.clone();
+        match transform::perspective(&mut bad, *quad, Color::red()) {
+            Err(raster::error::RasterError::InvalidMatrix) => {}
+            _ => panic!("expected InvalidMatrix for {:?}", quad),
+        }
+        assert!(bad.is_equal(&image));
+    }
+}